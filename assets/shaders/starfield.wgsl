@@ -3,7 +3,13 @@
 
 struct StarfieldMaterial {
     pos: vec2<f32>,
-    _wasm_padding: vec2<f32>,
+    time: f32,
+    layer_count: u32,
+    nebula_color: vec4<f32>,
+    nebula_strength: f32,
+    layer_colors: array<vec4<f32>, 8>,
+    // parallax, density, twinkle, unused
+    layer_params: array<vec4<f32>, 8>,
 };
 
 @group(1) @binding(0)
@@ -49,30 +55,60 @@ fn starfield(samplePosition: vec2<f32>, threshold: f32) -> vec3<f32> {
 	return vec3<f32>(power);
 }
 
+// Smooth noise for the nebula, from 0 to 1
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3. - 2. * f);
+
+    let a = hash22(i).x;
+    let b = hash22(i + vec2<f32>(1., 0.)).x;
+    let c = hash22(i + vec2<f32>(0., 1.)).x;
+    let d = hash22(i + vec2<f32>(1., 1.)).x;
+
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn nebula(p: vec2<f32>) -> f32 {
+    var value: f32 = 0.;
+    var amplitude: f32 = 0.5;
+    var q: vec2<f32> = p;
+
+    for (var i: i32 = 0; i < 4; i = i + 1) {
+        value = value + value_noise(q) * amplitude;
+        q = q * 2.03 + vec2<f32>(17.1, 9.2);
+        amplitude = amplitude * 0.5;
+    }
+
+    return value;
+}
+
 @fragment
 fn fragment(
     mesh: MeshVertexOutput
 ) -> @location(0) vec4<f32> {
-	var finalColor: vec3<f32>;
+    var finalColor: vec3<f32>;
 
     let pos = material.pos / vec2<f32>(-1000., 1000.);
     let threshold = 0.0003;
 
-	for (var i: i32 = 1; i <= 7; i = i + 1) {
-		let layer: f32 = f32(i);
-		let inv: f32 = sqrt(1. / layer);
+    // Far behind the stars, so it barely moves
+    let cloud = smoothstep(0.35, 0.9, nebula(mesh.position.xy / 700. - pos * 0.2));
+    finalColor = material.nebula_color.rgb * cloud * material.nebula_strength;
 
-        let layer_offset = vec2<f32>(layer * 100., -layer * 50.);
-        let layer_zoom = (1. + layer * 0.6) / 500.;
-        let layer_speed = inv;
-        let layer_brightness = inv * 0.4;
+    for (var i: u32 = 0u; i < material.layer_count; i = i + 1u) {
+        let params = material.layer_params[i];
+        let index = f32(i + 1u);
 
-        let starfield_coords = (mesh.position.xy + layer_offset) * layer_zoom - pos * layer_speed;
+        let layer_offset = vec2<f32>(index * 100., -index * 50.);
+        let starfield_coords = (mesh.position.xy + layer_offset) * params.y / 500. - pos * params.x;
 
-		finalColor = finalColor + (starfield(starfield_coords, threshold) * layer_brightness);
-	}
+        // Each star twinkles at its own pace
+        let phase = hash22(floor(starfield_coords)).x * 6.2831;
+        let twinkle = 1. - params.z * 0.5 * (1. + sin(material.time * 2.5 + phase));
 
-	let fragColor = vec4<f32>(finalColor, 1.);
+        finalColor = finalColor + starfield(starfield_coords, threshold) * material.layer_colors[i].rgb * twinkle;
+    }
 
-    return fragColor;
-}
\ No newline at end of file
+    return vec4<f32>(finalColor, 1.);
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+use crate::{
+    commodity::{CargoHold, CommodityInventory, CommodityKind},
+    direction_indicator::DirectionIndicatorSettings,
+    fuel::SpawnFuelPelletEvent,
+    layer,
+    run::RunSeed,
+    scanner::{ScanTargetKind, Scannable},
+    Credits, DespawnOnRestart, GameState, Player,
+};
+
+pub struct AnomalyPlugin;
+impl Plugin for AnomalyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), setup);
+        app.add_systems(Update, investigate.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AnomalyKind {
+    DerelictShip,
+    AsteroidField,
+    HiddenCache,
+}
+
+impl AnomalyKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::DerelictShip => "Derelict",
+            Self::AsteroidField => "Asteroids",
+            Self::HiddenCache => "Cache",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::DerelictShip => Color::GRAY,
+            Self::AsteroidField => Color::SILVER,
+            Self::HiddenCache => Color::CYAN,
+        }
+    }
+
+    fn scannable(&self) -> Scannable {
+        let (priority, difficulty) = match self {
+            Self::DerelictShip => (1, 1.),
+            Self::AsteroidField => (0, 0.75),
+            // Caches are small and well hidden, so the player has to get close
+            Self::HiddenCache => (2, 3.),
+        };
+
+        Scannable {
+            kind: ScanTargetKind::PointOfInterest,
+            priority,
+            difficulty,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Anomaly(AnomalyKind);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    seed: Res<RunSeed>,
+) {
+    let mut rng = seed.rng("anomaly");
+
+    let num = rng.gen_range(1..=3);
+
+    let kinds = [
+        AnomalyKind::DerelictShip,
+        AnomalyKind::AsteroidField,
+        AnomalyKind::HiddenCache,
+    ];
+
+    for _ in 0..num {
+        let kind = *kinds.choose(&mut rng).unwrap();
+
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(800.0..2500.0);
+        let pos = (Vec2::from_angle(angle) * distance).extend(layer::OBJECT);
+
+        let material = materials.add(kind.color().into());
+
+        let mut entity = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(pos)),
+            Anomaly(kind),
+            DirectionIndicatorSettings {
+                color: kind.color(),
+                label: Some(kind.label().to_string()),
+            },
+            kind.scannable(),
+            DespawnOnRestart,
+        ));
+
+        entity.with_children(|parent| match kind {
+            AnomalyKind::DerelictShip => {
+                parent.spawn(ColorMesh2dBundle {
+                    mesh: meshes.add(shape::RegularPolygon::new(20., 3).into()).into(),
+                    material,
+                    transform: Transform::from_rotation(Quat::from_rotation_z(
+                        rng.gen_range(0.0..std::f32::consts::TAU),
+                    )),
+                    ..default()
+                });
+            }
+            AnomalyKind::AsteroidField => {
+                for _ in 0..rng.gen_range(5..9) {
+                    let offset = Vec2::new(rng.gen_range(-60.0..60.), rng.gen_range(-60.0..60.));
+
+                    parent.spawn(ColorMesh2dBundle {
+                        mesh: meshes
+                            .add(shape::Circle::new(rng.gen_range(4.0..12.0)).into())
+                            .into(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(offset.extend(0.)),
+                        ..default()
+                    });
+                }
+            }
+            AnomalyKind::HiddenCache => {
+                parent.spawn(ColorMesh2dBundle {
+                    mesh: meshes.add(shape::Quad::new(Vec2::splat(10.)).into()).into(),
+                    material,
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+fn investigate(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform, &Anomaly)>,
+    mut player_query: Query<
+        (
+            &Transform,
+            &mut Credits,
+            &mut CommodityInventory,
+            &CargoHold,
+        ),
+        With<Player>,
+    >,
+    mut fuel_events: EventWriter<SpawnFuelPelletEvent>,
+) {
+    let (player_transform, mut credits, mut inventory, hold) = player_query.single_mut();
+
+    let mut rng = thread_rng();
+
+    for (entity, transform, anomaly) in query.iter() {
+        let location = transform.translation.truncate();
+
+        if location.distance(player_transform.translation.truncate()) > 40. {
+            continue;
+        }
+
+        match anomaly.0 {
+            AnomalyKind::DerelictShip => {
+                // Salvage whatever is left in the derelict's tanks
+                for _ in 0..rng.gen_range(3..=6) {
+                    let offset = Vec2::new(rng.gen_range(-30.0..30.), rng.gen_range(-30.0..30.));
+                    fuel_events.send(SpawnFuelPelletEvent {
+                        location: location + offset,
+                    });
+                }
+            }
+            AnomalyKind::AsteroidField => {
+                let kind = [
+                    CommodityKind::Tungsten,
+                    CommodityKind::Gallium,
+                    CommodityKind::Thorium,
+                ]
+                .choose(&mut rng)
+                .unwrap()
+                .clone();
+                let amount = rng.gen_range(5..=15);
+
+                inventory.add(kind, amount, hold);
+            }
+            AnomalyKind::HiddenCache => {
+                credits.0 += rng.gen_range(25..=75);
+            }
+        }
+
+        commands.entity(entity).despawn_recursive();
+    }
+}
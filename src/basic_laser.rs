@@ -1,6 +1,9 @@
 use bevy::{prelude::*, utils::HashSet};
 
-use crate::{enemy::Enemy, layer, DespawnOnRestart, GameState, Health, Velocity};
+use crate::{
+    combat_feedback::HitEvent, enemy::Enemy, layer, physics::Interpolated, DespawnOnRestart,
+    GameState, Health, Velocity,
+};
 
 pub struct BasicLaserPlugin;
 
@@ -28,15 +31,23 @@ struct Range(f32);
 #[derive(Component)]
 struct Origin(Vec2);
 
-fn fire(mut commands: Commands, mut query: Query<(&mut BasicLaser, &Transform)>, time: Res<Time>) {
-    for (mut gun, transform) in query.iter_mut() {
+fn fire(
+    mut commands: Commands,
+    mut query: Query<(&mut BasicLaser, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    for (mut gun, global_transform) in query.iter_mut() {
         gun.timer.tick(time.delta());
         if !gun.timer.just_finished() {
             continue;
         }
 
-        let rot = transform.rotation;
-        let trans = rot.mul_vec3(Vec3::new(25., 0., 0.)) + transform.translation;
+        // Guns are mounted on their ship, so go by where they are in the world
+        let (_, rot, translation) = global_transform.to_scale_rotation_translation();
+        let trans = rot.mul_vec3(Vec3::new(25., 0., 0.)) + translation;
+
+        let transform =
+            Transform::from_translation(trans.truncate().extend(layer::BULLET)).with_rotation(rot);
 
         commands.spawn((
             SpriteBundle {
@@ -45,10 +56,10 @@ fn fire(mut commands: Commands, mut query: Query<(&mut BasicLaser, &Transform)>,
                     custom_size: Some(Vec2::new(10., 2.)),
                     ..default()
                 },
-                transform: Transform::from_translation(trans.truncate().extend(layer::BULLET))
-                    .with_rotation(rot),
+                transform,
                 ..default()
             },
+            Interpolated::new(&transform),
             Origin(trans.truncate()),
             Range(400.),
             Bullet {
@@ -64,7 +75,8 @@ fn fire(mut commands: Commands, mut query: Query<(&mut BasicLaser, &Transform)>,
 fn collide(
     mut commands: Commands,
     bullet_query: Query<(Entity, &Transform, &Bullet)>,
-    mut enemy_query: Query<(&Transform, &mut Health), With<Enemy>>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Health), With<Enemy>>,
+    mut events: EventWriter<HitEvent>,
 ) {
     let mut used_bullets = HashSet::new();
 
@@ -72,7 +84,7 @@ fn collide(
         if used_bullets.contains(&bullet_entity) {
             continue;
         }
-        for (enemy_transform, mut health) in enemy_query.iter_mut() {
+        for (enemy_entity, enemy_transform, mut health) in enemy_query.iter_mut() {
             if enemy_transform
                 .translation
                 .truncate()
@@ -80,6 +92,11 @@ fn collide(
                 < 10.
             {
                 health.current -= bullet.damage;
+                events.send(HitEvent {
+                    entity: enemy_entity,
+                    position: enemy_transform.translation.truncate(),
+                    damage: bullet.damage,
+                });
 
                 if !bullet.piercing {
                     used_bullets.insert(bullet_entity);
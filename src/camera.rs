@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    combat_feedback::DeathEvent, physics, shield::DamageEvent, GameState, MovementSet, Player,
+    Velocity,
+};
+
+pub struct CameraControllerPlugin;
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Loading), spawn_camera);
+        app.add_systems(OnEnter(GameState::Playing), snap);
+        app.add_systems(
+            Update,
+            (
+                add_trauma.run_if(in_state(GameState::Playing)),
+                move_camera
+                    .after(physics::interpolate)
+                    .after(add_trauma)
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Warping)))
+                    .in_set(MovementSet),
+            ),
+        );
+    }
+}
+
+/// Seconds of travel the camera looks ahead of the ship
+const LOOK_AHEAD: f32 = 0.5;
+pub const MAX_LOOK_AHEAD: f32 = 150.;
+/// How quickly the camera catches up with where it wants to be
+const SMOOTHING: f32 = 4.;
+/// Extra zoom per unit of speed
+const ZOOM_PER_SPEED: f32 = 0.003;
+const MAX_ZOOM: f32 = 1.5;
+const ZOOM_SMOOTHING: f32 = 1.5;
+/// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.2;
+const MAX_SHAKE_OFFSET: f32 = 14.;
+const MAX_SHAKE_ANGLE: f32 = 0.04;
+/// Trauma from an explosion right next to the ship, falling off with distance
+const EXPLOSION_TRAUMA: f32 = 0.25;
+const EXPLOSION_TRAUMA_RANGE: f32 = 300.;
+/// Trauma per point of damage taken
+const DAMAGE_TRAUMA: f32 = 0.04;
+
+#[derive(Component, Default)]
+pub struct CameraController {
+    /// Where the camera is looking, before any shake
+    pub focus: Vec2,
+    /// From 0 to 1. Shake grows with the square of this, so that small knocks
+    /// are subtle and big hits are violent.
+    pub trauma: f32,
+}
+
+impl CameraController {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    // Visibility lets HUD elements like the radar be children of the camera
+    commands.spawn((
+        Camera2dBundle::default(),
+        VisibilityBundle::default(),
+        CameraController::default(),
+    ));
+}
+
+/// Jumps straight to the player when a system starts, rather than panning over
+/// from wherever the last one ended
+fn snap(
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<
+        (
+            &mut CameraController,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        Without<Player>,
+    >,
+) {
+    let player = player_query.single();
+    let (mut controller, mut transform, mut projection) = camera_query.single_mut();
+
+    controller.focus = player.translation.truncate();
+    controller.trauma = 0.;
+
+    transform.translation.x = controller.focus.x;
+    transform.translation.y = controller.focus.y;
+    transform.rotation = Quat::IDENTITY;
+    projection.scale = 1.;
+}
+
+fn add_trauma(
+    mut damage_events: EventReader<DamageEvent>,
+    mut death_events: EventReader<DeathEvent>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut camera_query: Query<&mut CameraController>,
+) {
+    let (player_entity, player) = player_query.single();
+    let mut controller = camera_query.single_mut();
+
+    for event in damage_events.iter() {
+        if event.entity == player_entity {
+            controller.add_trauma(event.amount * DAMAGE_TRAUMA);
+        }
+    }
+
+    for event in death_events.iter() {
+        let dist = event.position.distance(player.translation.truncate());
+        let falloff = 1. - (dist / EXPLOSION_TRAUMA_RANGE).min(1.);
+
+        if falloff > 0. {
+            controller.add_trauma(EXPLOSION_TRAUMA * falloff);
+        }
+    }
+}
+
+fn move_camera(
+    time: Res<Time>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    mut camera_query: Query<
+        (
+            &mut CameraController,
+            &mut Transform,
+            &mut OrthographicProjection,
+        ),
+        Without<Player>,
+    >,
+) {
+    let (player, velocity) = player_query.single();
+    let (mut controller, mut transform, mut projection) = camera_query.single_mut();
+
+    let dt = time.delta_seconds();
+
+    // Paused, so hold the view still rather than shaking in place
+    if dt == 0. {
+        return;
+    }
+
+    let look_ahead = (velocity.0 * LOOK_AHEAD).clamp_length_max(MAX_LOOK_AHEAD);
+    let target = player.translation.truncate() + look_ahead;
+    controller.focus = controller.focus.lerp(target, 1. - (-SMOOTHING * dt).exp());
+
+    let target_zoom = (1. + velocity.0.length() * ZOOM_PER_SPEED).min(MAX_ZOOM);
+    projection.scale += (target_zoom - projection.scale) * (1. - (-ZOOM_SMOOTHING * dt).exp());
+
+    controller.trauma = (controller.trauma - TRAUMA_DECAY * dt).max(0.);
+    let shake = controller.trauma * controller.trauma;
+
+    let mut rng = thread_rng();
+    let offset =
+        Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)) * MAX_SHAKE_OFFSET * shake;
+    let angle = rng.gen_range(-1.0..=1.0) * MAX_SHAKE_ANGLE * shake;
+
+    let pos = controller.focus + offset;
+    transform.translation.x = pos.x;
+    transform.translation.y = pos.y;
+    transform.rotation = Quat::from_rotation_z(angle);
+}
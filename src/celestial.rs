@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    direction_indicator::{DirectionIndicator, DirectionIndicatorSettings},
+    gravity::Mass,
+    layer,
+    physics::Interpolated,
+    run::RunSeed,
+    DespawnOnRestart, GameState, PhysicsSet, Planet,
+};
+
+pub struct CelestialPlugin;
+impl Plugin for CelestialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_system);
+        app.add_systems(
+            FixedUpdate,
+            (advance_orbits, apply_orbits.after(advance_orbits))
+                .before(crate::movement)
+                .run_if(in_state(GameState::Playing))
+                .in_set(PhysicsSet),
+        );
+    }
+}
+
+/// Moves an entity in a circle around the system's star, or around another
+/// orbiting body.
+#[derive(Component)]
+pub struct Orbit {
+    /// The body being orbited, or the star at the origin if `None`
+    pub center: Option<Entity>,
+    pub radius: f32,
+    pub angle: f32,
+    /// Radians per second
+    pub speed: f32,
+}
+
+impl Orbit {
+    /// Where the body is relative to what it orbits
+    fn offset(&self) -> Vec2 {
+        Vec2::from_angle(self.angle) * self.radius
+    }
+}
+
+#[derive(Component)]
+pub struct Star;
+
+/// Converts a body's radius squared into its gravitational parameter
+const DENSITY: f32 = 375.;
+
+const PLANET_COLORS: [Color; 6] = [
+    Color::AQUAMARINE,
+    Color::TEAL,
+    Color::SALMON,
+    Color::OLIVE,
+    Color::ORANGE_RED,
+    Color::VIOLET,
+];
+
+fn spawn_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    seed: Res<RunSeed>,
+) {
+    let mut rng = seed.rng("celestial");
+
+    commands.spawn((
+        ColorMesh2dBundle {
+            mesh: meshes.add(shape::Circle::new(120.).into()).into(),
+            material: materials.add(Color::YELLOW.into()),
+            transform: Transform::from_xyz(0., 0., layer::PLANET),
+            ..default()
+        },
+        Star,
+        Mass(120. * 120. * DENSITY),
+        DespawnOnRestart,
+    ));
+
+    let num_planets = rng.gen_range(2..=4);
+    // The belt goes inside one of the planets' orbits, or outside them all
+    let belt_after = rng.gen_range(0..=num_planets);
+
+    let mut colors = PLANET_COLORS.to_vec();
+    colors.shuffle(&mut rng);
+
+    let mut orbit_radius = 300.;
+
+    for i in 0..num_planets {
+        orbit_radius += rng.gen_range(350.0..550.0);
+
+        if i == belt_after {
+            spawn_asteroid_belt(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &mut rng,
+                orbit_radius,
+            );
+            orbit_radius += rng.gen_range(250.0..350.0);
+        }
+
+        let color = colors[i % colors.len()];
+        let radius = rng.gen_range(25.0..60.0);
+
+        let orbit = Orbit {
+            center: None,
+            radius: orbit_radius,
+            angle: rng.gen_range(0.0..std::f32::consts::TAU),
+            // Outer planets orbit more slowly
+            speed: 4. / orbit_radius,
+        };
+        let planet_pos = orbit.offset();
+        // Placed on its orbit straight away, so that it isn't smoothed in
+        // from the star on the first frame
+        let transform = Transform::from_translation(planet_pos.extend(layer::PLANET));
+
+        let planet = commands
+            .spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                    material: materials.add(color.into()),
+                    transform,
+                    ..default()
+                },
+                orbit,
+                Interpolated::new(&transform),
+                Planet,
+                Mass(radius * radius * DENSITY),
+                DespawnOnRestart,
+            ))
+            .id();
+
+        commands.spawn((
+            DirectionIndicator {
+                target: planet,
+                settings: DirectionIndicatorSettings { color, label: None },
+            },
+            DespawnOnRestart,
+        ));
+
+        let mut moon_radius = radius;
+
+        for _ in 0..rng.gen_range(0..=2) {
+            moon_radius += rng.gen_range(25.0..50.0);
+            let size = rng.gen_range(5.0..12.0);
+
+            let orbit = Orbit {
+                center: Some(planet),
+                radius: moon_radius,
+                angle: rng.gen_range(0.0..std::f32::consts::TAU),
+                speed: rng.gen_range(0.2..0.5),
+            };
+            let transform = Transform::from_translation(
+                (planet_pos + orbit.offset()).extend(layer::PLANET + 0.1),
+            );
+
+            commands.spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes.add(shape::Circle::new(size).into()).into(),
+                    material: materials.add(Color::GRAY.into()),
+                    transform,
+                    ..default()
+                },
+                orbit,
+                Interpolated::new(&transform),
+                DespawnOnRestart,
+            ));
+        }
+    }
+
+    if belt_after == num_planets {
+        orbit_radius += rng.gen_range(350.0..550.0);
+
+        spawn_asteroid_belt(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut rng,
+            orbit_radius,
+        );
+    }
+}
+
+fn spawn_asteroid_belt(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    rng: &mut impl Rng,
+    orbit_radius: f32,
+) {
+    let material = materials.add(Color::DARK_GRAY.into());
+
+    for _ in 0..80 {
+        let radius = orbit_radius + rng.gen_range(-80.0..80.0);
+        let size = rng.gen_range(3.0..9.0);
+
+        let orbit = Orbit {
+            center: None,
+            radius,
+            angle: rng.gen_range(0.0..std::f32::consts::TAU),
+            speed: 4. / radius,
+        };
+        let transform = Transform::from_translation(orbit.offset().extend(layer::PLANET));
+
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: meshes
+                    .add(shape::RegularPolygon::new(size, 5).into())
+                    .into(),
+                material: material.clone(),
+                transform,
+                ..default()
+            },
+            orbit,
+            Interpolated::new(&transform),
+            DespawnOnRestart,
+        ));
+    }
+}
+
+fn advance_orbits(fixed_time: Res<FixedTime>, mut query: Query<&mut Orbit>) {
+    for mut orbit in query.iter_mut() {
+        orbit.angle += orbit.speed * fixed_time.period.as_secs_f32();
+    }
+}
+
+/// The position of an orbiting body, relative to the star
+fn orbit_position(orbit: &Orbit, orbits: &Query<&Orbit>) -> Vec2 {
+    let center = orbit
+        .center
+        .and_then(|center| orbits.get(center).ok())
+        .map_or(Vec2::ZERO, |center| orbit_position(center, orbits));
+
+    center + orbit.offset()
+}
+
+fn apply_orbits(mut query: Query<(&Orbit, &mut Transform)>, orbits: Query<&Orbit>) {
+    for (orbit, mut transform) in query.iter_mut() {
+        let pos = orbit_position(orbit, &orbits);
+
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y;
+    }
+}
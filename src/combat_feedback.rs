@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    layer,
+    particle::{ParticleBurstEvent, ParticleSettings},
+    pool::{release_all, Pool, Slot},
+    Fonts, GameState,
+};
+
+pub struct CombatFeedbackPlugin;
+impl Plugin for CombatFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitEvent>().add_event::<DeathEvent>();
+        app.insert_resource(Pool::<DamageNumber>::new(MAX_DAMAGE_NUMBERS));
+
+        app.add_systems(OnEnter(GameState::Playing), release_all::<DamageNumber>);
+        app.add_systems(OnEnter(GameState::MainMenu), release_all::<DamageNumber>);
+        app.add_systems(
+            Update,
+            (hit, death, flash).run_if(in_state(GameState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            animate.run_if(in_state(GameState::Playing).or_else(in_state(GameState::Warping))),
+        );
+    }
+}
+
+/// Most damage numbers alive at once. When lots of things are hit together
+/// the extra numbers are skipped, since they'd only pile on top of each other.
+const MAX_DAMAGE_NUMBERS: usize = 100;
+
+const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+const DAMAGE_NUMBER_SPEED: f32 = 40.;
+const IMPACT_PARTICLES: usize = 5;
+const DEATH_PARTICLES: usize = 16;
+const FLASH_DURATION: f32 = 0.1;
+
+/// A bullet hit something
+#[derive(Event)]
+pub struct HitEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub damage: f32,
+}
+
+/// Something was destroyed
+#[derive(Event)]
+pub struct DeathEvent {
+    pub position: Vec2,
+    pub color: Color,
+}
+
+/// Turns a sprite white for a moment after it's hit. Kept on the entity so
+/// that hits don't add or remove components.
+#[derive(Component)]
+pub struct HitFlash {
+    timer: Timer,
+    /// The sprite's own colour, restored after the flash
+    pub color: Color,
+}
+
+impl HitFlash {
+    pub fn new(color: Color) -> Self {
+        let mut timer = Timer::from_seconds(FLASH_DURATION, TimerMode::Once);
+        timer.tick(timer.duration());
+
+        Self { timer, color }
+    }
+}
+
+/// Floats up and fades out. Hidden when not in use.
+#[derive(Component)]
+struct DamageNumber {
+    timer: Timer,
+    velocity: Vec2,
+}
+
+fn hit(
+    mut commands: Commands,
+    mut events: EventReader<HitEvent>,
+    mut pool: ResMut<Pool<DamageNumber>>,
+    mut query: Query<(
+        &mut DamageNumber,
+        &mut Transform,
+        &mut Visibility,
+        &mut Text,
+    )>,
+    mut flash_query: Query<(&mut HitFlash, &mut Sprite)>,
+    mut particle_events: EventWriter<ParticleBurstEvent>,
+    fonts: Res<Fonts>,
+) {
+    let mut rng = thread_rng();
+
+    for event in events.iter() {
+        if let Ok((mut flash, mut sprite)) = flash_query.get_mut(event.entity) {
+            flash.timer.reset();
+            sprite.color = Color::WHITE;
+        }
+
+        particle_events.send(ParticleBurstEvent {
+            position: event.position,
+            rotation: 0.,
+            count: IMPACT_PARTICLES,
+            settings: ParticleSettings::impact(),
+        });
+
+        let number = DamageNumber {
+            timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once),
+            velocity: Vec2::new(rng.gen_range(-10.0..10.0), DAMAGE_NUMBER_SPEED),
+        };
+        let transform = Transform::from_translation(
+            (event.position + Vec2::new(0., 12.)).extend(layer::EFFECT),
+        );
+        let value = format!("{}", event.damage);
+
+        match pool.acquire() {
+            Some(Slot::Reuse(entity)) => {
+                let Ok((mut old_number, mut old_transform, mut visibility, mut text)) =
+                    query.get_mut(entity)
+                else {
+                    continue;
+                };
+
+                *old_number = number;
+                *old_transform = transform;
+                *visibility = Visibility::Visible;
+                text.sections[0].value = value;
+                text.sections[0].style.color = Color::WHITE;
+            }
+            Some(Slot::Spawn) => {
+                commands.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            value,
+                            TextStyle {
+                                font: fonts.main.clone(),
+                                font_size: 14.,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        transform,
+                        ..default()
+                    },
+                    number,
+                ));
+            }
+            None => {}
+        }
+    }
+}
+
+fn death(
+    mut events: EventReader<DeathEvent>,
+    mut particle_events: EventWriter<ParticleBurstEvent>,
+) {
+    for event in events.iter() {
+        particle_events.send(ParticleBurstEvent {
+            position: event.position,
+            rotation: 0.,
+            count: DEATH_PARTICLES,
+            settings: ParticleSettings::explosion(event.color),
+        });
+    }
+}
+
+fn flash(time: Res<Time>, mut query: Query<(&mut HitFlash, &mut Sprite)>) {
+    for (mut flash, mut sprite) in query.iter_mut() {
+        if flash.timer.finished() {
+            continue;
+        }
+
+        flash.timer.tick(time.delta());
+        if flash.timer.just_finished() {
+            sprite.color = flash.color;
+        }
+    }
+}
+
+/// Moves and fades active damage numbers, returning them to the pool when done
+fn animate(
+    time: Res<Time>,
+    mut pool: ResMut<Pool<DamageNumber>>,
+    mut query: Query<(
+        Entity,
+        &mut DamageNumber,
+        &mut Transform,
+        &mut Visibility,
+        &mut Text,
+    )>,
+) {
+    for (entity, mut number, mut transform, mut visibility, mut text) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            *visibility = Visibility::Hidden;
+            pool.release(entity);
+            continue;
+        }
+
+        transform.translation += (number.velocity * time.delta_seconds()).extend(0.);
+
+        let alpha = number.timer.percent_left();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
@@ -1,13 +1,16 @@
 use bevy::{prelude::*, utils::HashMap};
 use indexmap::IndexMap;
 use itertools::izip;
-use rand::{distributions::Uniform, seq::IteratorRandom, thread_rng, Rng};
+use rand::{distributions::Uniform, seq::IteratorRandom, Rng};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::{
-    direction_indicator::DirectionIndicatorSettings, layer, scanner::Scanner, util,
-    DespawnOnRestart, GameState, Player,
+    direction_indicator::DirectionIndicatorSettings,
+    layer,
+    run::RunSeed,
+    scanner::{ScanTargetKind, Scannable},
+    util, DespawnOnRestart, GameState, Player,
 };
 
 #[derive(EnumIter, Clone, Debug, PartialEq, Eq, Hash)]
@@ -29,21 +32,45 @@ pub struct Commodity {
     amount: u32,
 }
 
+#[derive(Event)]
+pub struct CommodityPickedUpEvent {
+    pub entity: Entity,
+}
+
 #[derive(Component, Default)]
 pub struct CommodityInventory(pub HashMap<CommodityKind, u32>);
 
+impl CommodityInventory {
+    pub fn total(&self) -> u32 {
+        self.0.values().sum()
+    }
+
+    /// Adds as much of `amount` as fits in `hold`, returning how much was added
+    pub fn add(&mut self, kind: CommodityKind, amount: u32, hold: &CargoHold) -> u32 {
+        let added = amount.min(hold.0.saturating_sub(self.total()));
+
+        if added > 0 {
+            *self.0.entry(kind).or_insert(0) += added;
+        }
+
+        added
+    }
+}
+
+/// How many units of commodities the ship can carry
+#[derive(Component)]
+pub struct CargoHold(pub u32);
+
 #[derive(Component, Default, Clone)]
 pub struct CommodityPrices(pub IndexMap<CommodityKind, f32>);
 
 impl CommodityPrices {
-    pub fn new_random() -> Self {
-        let mut rng = thread_rng();
-
+    pub fn new_random(rng: &mut impl Rng) -> Self {
         let num = rng.gen_range(2..=3);
 
         let mut prices = IndexMap::new();
 
-        let mut commodities = CommodityKind::iter().choose_multiple(&mut rng, num);
+        let mut commodities = CommodityKind::iter().choose_multiple(rng, num);
 
         for commodity in commodities.drain(0..) {
             let sign = if rng.gen() { 1. } else { -1. };
@@ -62,6 +89,7 @@ impl CommodityPrices {
 pub struct CommodityPlugin;
 impl Plugin for CommodityPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<CommodityPickedUpEvent>();
         app.add_systems(OnEnter(GameState::Playing), setup);
         app.add_systems(Update, pickup.run_if(in_state(GameState::Playing)));
     }
@@ -71,64 +99,72 @@ pub fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut scanner: ResMut<Scanner>,
+    seed: Res<RunSeed>,
 ) {
-    let mut rng = thread_rng();
+    let mut rng = seed.rng("commodity");
 
     let num = 3;
 
     let dist_range = Uniform::from(1500.0..2500.0);
     //let dist_range = Uniform::from(500.0..501.0);
 
-    let amounts = util::random_u32_subdivisions(num, 100, 20);
+    let amounts = util::random_u32_subdivisions(&mut rng, num, 100, 20);
     let kinds = CommodityKind::iter().choose_multiple(&mut rng, num as usize);
+    let angles = util::random_circular_f32_distribution(&mut rng, num, 80., 360.);
     let distances = rng.sample_iter(&dist_range).take(num as usize);
-    let angles = util::random_circular_f32_distribution(num, 80., 360.);
 
     for (angle, distance, amount, kind) in izip!(angles, distances, amounts, kinds) {
         let angle = angle.to_radians();
         let (y, x) = angle.sin_cos();
         let pos = Vec3::new(x * distance, y * distance, layer::OBJECT);
 
-        let entity = commands
-            .spawn((
-                ColorMesh2dBundle {
-                    mesh: meshes.add(shape::RegularPolygon::new(10., 5).into()).into(),
-                    material: materials.add(Color::BEIGE.into()),
-                    transform: Transform::from_translation(pos),
-                    ..default()
-                },
-                Commodity { kind, amount },
-                DirectionIndicatorSettings {
-                    color: Color::BEIGE,
-                    label: None,
-                },
-                DespawnOnRestart,
-            ))
-            .id();
-
-        scanner.commodities.push(entity);
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: meshes.add(shape::RegularPolygon::new(10., 5).into()).into(),
+                material: materials.add(Color::BEIGE.into()),
+                transform: Transform::from_translation(pos),
+                ..default()
+            },
+            Commodity { kind, amount },
+            DirectionIndicatorSettings {
+                color: Color::BEIGE,
+                label: None,
+            },
+            Scannable {
+                kind: ScanTargetKind::Commodity,
+                priority: 0,
+                difficulty: 1.,
+            },
+            DespawnOnRestart,
+        ));
     }
 }
 
 fn pickup(
     mut commands: Commands,
-    query: Query<(Entity, &Transform, &Commodity)>,
-    mut player_query: Query<(&Transform, &mut CommodityInventory), With<Player>>,
+    mut query: Query<(Entity, &Transform, &mut Commodity)>,
+    mut player_query: Query<(&Transform, &mut CommodityInventory, &CargoHold), With<Player>>,
+    mut events: EventWriter<CommodityPickedUpEvent>,
 ) {
-    let (player_transform, mut inventory) = player_query.single_mut();
-    for (entity, transform, commodity) in query.iter() {
+    let (player_transform, mut inventory, hold) = player_query.single_mut();
+    for (entity, transform, mut commodity) in query.iter_mut() {
         if transform
             .translation
             .truncate()
             .distance(player_transform.translation.truncate())
             < 20.
         {
-            inventory
-                .0
-                .entry(commodity.kind.clone())
-                .and_modify(|e| *e += commodity.amount)
-                .or_insert(commodity.amount);
+            let added = inventory.add(commodity.kind.clone(), commodity.amount, hold);
+
+            // Whatever doesn't fit stays behind for later
+            if added < commodity.amount {
+                if added > 0 {
+                    commodity.amount -= added;
+                }
+                continue;
+            }
+
+            events.send(CommodityPickedUpEvent { entity });
 
             commands.entity(entity).despawn();
         }
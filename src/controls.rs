@@ -0,0 +1,660 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{prelude::*, user_input::InputKind};
+
+use crate::{Action, Fonts, GameState, Player};
+
+pub struct ControlsPlugin;
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Controls::load());
+        app.init_resource::<ControlsMenu>();
+        app.add_event::<OpenControlsEvent>();
+
+        app.add_systems(
+            Update,
+            (
+                toggle,
+                click.after(toggle),
+                listen.after(click),
+                apply.after(listen),
+                refresh.after(listen),
+            )
+                .run_if(in_state(GameState::Playing).or_else(in_state(GameState::MainMenu))),
+        );
+    }
+}
+
+/// Where the player's bindings are saved, relative to the working directory
+const CONTROLS_PATH: &str = "controls.ron";
+
+/// Actions that can be rebound from the controls screen. Analog turning and
+/// aiming stay on the sticks.
+const REBINDABLE: [Action; 20] = [
+    Action::TurnLeft,
+    Action::TurnRight,
+    Action::Forward,
+    Action::Reverse,
+    Action::StrafeLeft,
+    Action::StrafeRight,
+    Action::Boost,
+    Action::ToggleDampers,
+    Action::ToggleMouseSteering,
+    Action::CycleScanMode,
+    Action::ScannerPing,
+    Action::UpgradeScanner,
+    Action::Repair,
+    Action::ToggleGravity,
+    Action::CycleRadarRange,
+    Action::CycleRadarFilter,
+    Action::PinWaypoint,
+    Action::ToggleAutopilot,
+    Action::ToggleEnemyStatus,
+    Action::OpenShop,
+];
+
+/// Opens and closes the controls screen. Not rebindable, so that the screen
+/// can't be locked out.
+const MENU_KEY: KeyCode = KeyCode::F1;
+
+/// Bindings shown per action and device on the controls screen
+const SLOTS: usize = 2;
+
+/// The player's input bindings
+#[derive(Resource, Clone)]
+pub struct Controls(pub InputMap<Action>);
+
+impl Default for Controls {
+    fn default() -> Self {
+        let mut input_map = InputMap::new([
+            (KeyCode::A, Action::TurnLeft),
+            (KeyCode::Left, Action::TurnLeft),
+            (KeyCode::D, Action::TurnRight),
+            (KeyCode::Right, Action::TurnRight),
+            (KeyCode::W, Action::Forward),
+            (KeyCode::Up, Action::Forward),
+            (KeyCode::S, Action::Reverse),
+            (KeyCode::Down, Action::Reverse),
+            (KeyCode::Z, Action::StrafeLeft),
+            (KeyCode::C, Action::StrafeRight),
+            (KeyCode::ShiftLeft, Action::Boost),
+            (KeyCode::X, Action::ToggleDampers),
+            (KeyCode::M, Action::ToggleMouseSteering),
+            (KeyCode::Q, Action::CycleScanMode),
+            (KeyCode::E, Action::ScannerPing),
+            (KeyCode::U, Action::UpgradeScanner),
+            (KeyCode::R, Action::Repair),
+            (KeyCode::G, Action::ToggleGravity),
+            (KeyCode::V, Action::CycleRadarRange),
+            (KeyCode::B, Action::CycleRadarFilter),
+            (KeyCode::T, Action::PinWaypoint),
+            (KeyCode::P, Action::ToggleAutopilot),
+            (KeyCode::H, Action::ToggleEnemyStatus),
+            (KeyCode::O, Action::OpenShop),
+        ]);
+
+        input_map.insert_multiple([
+            (GamepadButtonType::DPadLeft, Action::TurnLeft),
+            (GamepadButtonType::DPadRight, Action::TurnRight),
+            (GamepadButtonType::DPadUp, Action::Forward),
+            (GamepadButtonType::DPadDown, Action::Reverse),
+            (GamepadButtonType::LeftTrigger, Action::StrafeLeft),
+            (GamepadButtonType::RightTrigger, Action::StrafeRight),
+            (GamepadButtonType::RightTrigger2, Action::Forward),
+            (GamepadButtonType::LeftTrigger2, Action::Reverse),
+            (GamepadButtonType::LeftThumb, Action::Boost),
+            (GamepadButtonType::East, Action::ToggleDampers),
+            (GamepadButtonType::North, Action::CycleScanMode),
+            (GamepadButtonType::South, Action::ScannerPing),
+            (GamepadButtonType::West, Action::UpgradeScanner),
+            (GamepadButtonType::RightThumb, Action::Repair),
+            (GamepadButtonType::Select, Action::ToggleGravity),
+        ]);
+
+        input_map.insert_multiple([
+            (MouseButton::Left, Action::Forward),
+            (MouseButton::Right, Action::Reverse),
+        ]);
+
+        input_map.insert(
+            SingleAxis::symmetric(GamepadAxisType::LeftStickX, 0.1),
+            Action::Turn,
+        );
+        input_map.insert(DualAxis::right_stick(), Action::Aim);
+        Self(input_map)
+    }
+}
+
+impl Controls {
+    /// Reads saved bindings, falling back to the defaults
+    fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(CONTROLS_PATH) else {
+            return Self::default();
+        };
+
+        match ron::from_str(&contents) {
+            Ok(input_map) => Self(input_map).with_defaults(),
+            Err(err) => {
+                warn!("Ignoring invalid {}: {}", CONTROLS_PATH, err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Fills in the default bindings for actions with none on a device, e.g.
+    /// actions added since the bindings were saved. Defaults that something
+    /// else is already bound to are left out.
+    fn with_defaults(mut self) -> Self {
+        let defaults = Self::default();
+
+        for action in Action::variants() {
+            let missing: Vec<UserInput> = if self.0.get(action).is_empty() {
+                defaults.0.get(action).iter().cloned().collect()
+            } else {
+                [Device::Keyboard, Device::Gamepad]
+                    .into_iter()
+                    .filter(|device| self.bindings(action, *device).is_empty())
+                    .flat_map(|device| defaults.bindings(action, device))
+                    .collect()
+            };
+
+            for input in missing {
+                if !self.0.iter_inputs().any(|inputs| inputs.contains(&input)) {
+                    self.0.insert(input, action);
+                }
+            }
+        }
+
+        self
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(&self.0, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                std::fs::write(CONTROLS_PATH, contents).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", CONTROLS_PATH, err);
+        }
+    }
+
+    /// The inputs bound to `action` on `device`
+    fn bindings(&self, action: Action, device: Device) -> Vec<UserInput> {
+        self.0
+            .get(action)
+            .iter()
+            .filter(|input| device.owns(input))
+            .cloned()
+            .collect()
+    }
+
+    /// Replaces the `slot`th input bound to `action` on `device`, or adds
+    /// another binding if the slot is empty
+    fn rebind(&mut self, action: Action, device: Device, slot: usize, input: UserInput) {
+        let old = self.bindings(action, device).get(slot).cloned();
+
+        match old.and_then(|old| self.0.remove(action, old)) {
+            Some(index) => {
+                self.0.insert_at(input, action, index);
+            }
+            None => {
+                self.0.insert(input, action);
+            }
+        }
+    }
+
+    /// Other actions sharing one of the inputs bound to `action` on `device`
+    fn conflicts(&self, action: Action, device: Device) -> Vec<Action> {
+        let bindings = self.bindings(action, device);
+
+        REBINDABLE
+            .iter()
+            .copied()
+            .filter(|other| *other != action)
+            .filter(|other| {
+                self.bindings(*other, device)
+                    .iter()
+                    .any(|input| bindings.contains(input))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Device {
+    Keyboard,
+    Gamepad,
+}
+
+impl Device {
+    fn owns(&self, input: &UserInput) -> bool {
+        matches!(
+            (self, input),
+            (Self::Keyboard, UserInput::Single(InputKind::Keyboard(_)))
+                | (
+                    Self::Gamepad,
+                    UserInput::Single(InputKind::GamepadButton(_))
+                )
+        )
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ControlsMenu {
+    root: Option<Entity>,
+    /// The binding waiting for the player to press something
+    listening: Option<(Action, Device, usize)>,
+    /// Whether actions were enabled before the menu was opened
+    actions_enabled: bool,
+    /// Whether the clock was already stopped before the menu was opened
+    time_paused: bool,
+}
+
+impl ControlsMenu {
+    pub fn is_open(&self) -> bool {
+        self.root.is_some()
+    }
+}
+
+/// Opens the controls screen from another menu
+#[derive(Event)]
+pub struct OpenControlsEvent;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    /// A binding slot
+    Binding(Action, Device, usize),
+    Reset,
+    Close,
+}
+
+#[derive(Component)]
+struct BindingLabel(Action, Device, usize);
+#[derive(Component)]
+struct ConflictLabel;
+
+fn toggle(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    mut events: EventReader<OpenControlsEvent>,
+    mut menu: ResMut<ControlsMenu>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    mut time: ResMut<Time>,
+    fonts: Res<Fonts>,
+) {
+    let requested = events.iter().count() > 0;
+
+    if menu.is_open() && keys.just_pressed(MENU_KEY) {
+        close(
+            &mut commands,
+            &mut menu,
+            &mut toggle_actions,
+            &mut time,
+            &mut keys,
+            &mut mouse_buttons,
+        );
+    } else if !menu.is_open() && (requested || keys.just_pressed(MENU_KEY)) {
+        menu.root = Some(spawn_menu(&mut commands, &fonts));
+        menu.actions_enabled = toggle_actions.enabled;
+        menu.time_paused = time.is_paused();
+        // Don't fly around, or get shot, while fiddling with the bindings
+        toggle_actions.enabled = false;
+        time.pause();
+    }
+}
+
+fn close(
+    commands: &mut Commands,
+    menu: &mut ControlsMenu,
+    toggle_actions: &mut ToggleActions<Action>,
+    time: &mut Time,
+    keys: &mut Input<KeyCode>,
+    mouse_buttons: &mut Input<MouseButton>,
+) {
+    if let Some(root) = menu.root.take() {
+        commands.entity(root).despawn_recursive();
+    }
+    menu.listening = None;
+    toggle_actions.enabled = menu.actions_enabled;
+    if !menu.time_paused {
+        time.unpause();
+    }
+
+    // Whatever closed the menu is still held, and shouldn't carry on into
+    // the game as e.g. a left click thrusting forward
+    keys.reset_all();
+    mouse_buttons.reset_all();
+}
+
+fn spawn_menu(commands: &mut Commands, fonts: &Fonts) -> Entity {
+    let text_style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 18.,
+        color: Color::BEIGE,
+    };
+
+    let button =
+        |parent: &mut ChildBuilder, kind: MenuButton, width: f32, label: Option<BindingLabel>| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(width),
+                            margin: UiRect::all(Val::Px(2.)),
+                            padding: UiRect::all(Val::Px(4.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    kind,
+                ))
+                .with_children(|parent| {
+                    let text = match kind {
+                        MenuButton::Reset => "Reset to Defaults",
+                        MenuButton::Close => "Close",
+                        MenuButton::Binding(..) => "",
+                    };
+                    let mut text = parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                    if let Some(label) = label {
+                        text.insert(label);
+                    }
+                });
+        };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                TextStyle {
+                    font_size: 30.,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Click a binding, then press a key or gamepad button. Esc cancels.",
+                text_style.clone(),
+            ));
+
+            for action in REBINDABLE {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(format!("{:?}", action), text_style.clone())
+                                .with_style(Style {
+                                    width: Val::Px(220.),
+                                    ..default()
+                                }),
+                        );
+
+                        for device in [Device::Keyboard, Device::Gamepad] {
+                            for slot in 0..SLOTS {
+                                button(
+                                    parent,
+                                    MenuButton::Binding(action, device, slot),
+                                    110.,
+                                    Some(BindingLabel(action, device, slot)),
+                                );
+                            }
+                        }
+                    });
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        color: Color::RED,
+                        ..text_style.clone()
+                    },
+                ),
+                ConflictLabel,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::top(Val::Px(10.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    button(parent, MenuButton::Reset, 220., None);
+                    button(parent, MenuButton::Close, 220., None);
+                });
+        })
+        .id()
+}
+
+fn click(
+    mut commands: Commands,
+    query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut menu: ResMut<ControlsMenu>,
+    mut controls: ResMut<Controls>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    mut time: ResMut<Time>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+) {
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            MenuButton::Binding(action, device, slot) => {
+                menu.listening = Some((action, device, slot))
+            }
+            MenuButton::Reset => {
+                *controls = Controls::default();
+                controls.save();
+            }
+            MenuButton::Close => close(
+                &mut commands,
+                &mut menu,
+                &mut toggle_actions,
+                &mut time,
+                &mut keys,
+                &mut mouse_buttons,
+            ),
+        }
+    }
+}
+
+fn listen(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut menu: ResMut<ControlsMenu>,
+    mut controls: ResMut<Controls>,
+) {
+    let Some((action, device, slot)) = menu.listening else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.listening = None;
+        return;
+    }
+
+    // The captured press is used up here, so that it doesn't also trigger
+    // whatever it's bound to
+    let input: Option<UserInput> = match device {
+        Device::Keyboard => {
+            let key = keys
+                .get_just_pressed()
+                .find(|key| **key != MENU_KEY)
+                .copied();
+            key.map(|key| {
+                keys.reset(key);
+                key.into()
+            })
+        }
+        Device::Gamepad => {
+            let button = gamepad_buttons.get_just_pressed().next().copied();
+            button.map(|button| {
+                gamepad_buttons.reset(button);
+                button.button_type.into()
+            })
+        }
+    };
+
+    let Some(input) = input else {
+        return;
+    };
+
+    controls.rebind(action, device, slot, input);
+    controls.save();
+
+    menu.listening = None;
+}
+
+/// Hands changed bindings to the player
+fn apply(controls: Res<Controls>, mut query: Query<&mut InputMap<Action>, With<Player>>) {
+    if !controls.is_changed() {
+        return;
+    }
+
+    for mut input_map in query.iter_mut() {
+        *input_map = controls.0.clone();
+    }
+}
+
+fn refresh(
+    controls: Res<Controls>,
+    menu: Res<ControlsMenu>,
+    mut label_query: Query<(&mut Text, &BindingLabel), Without<ConflictLabel>>,
+    mut conflict_query: Query<&mut Text, With<ConflictLabel>>,
+) {
+    if !controls.is_changed() && !menu.is_changed() {
+        return;
+    }
+
+    let mut conflicts = vec![];
+
+    for (mut text, BindingLabel(action, device, slot)) in label_query.iter_mut() {
+        let section = &mut text.sections[0];
+
+        let others = controls.conflicts(*action, *device);
+        for other in &others {
+            // Each conflict shows up once per side and slot, so only report
+            // one of them
+            if *other > *action && *slot == 0 {
+                conflicts.push(format!(
+                    "{:?} and {:?} share a {:?} binding",
+                    action, other, device
+                ));
+            }
+        }
+
+        if menu.listening == Some((*action, *device, *slot)) {
+            section.value = "Press...".to_string();
+            section.style.color = Color::YELLOW;
+            continue;
+        }
+
+        let binding = controls.bindings(*action, *device).get(*slot).cloned();
+
+        section.value = binding.as_ref().map_or_else(|| "-".to_string(), input_name);
+        section.style.color = match &binding {
+            Some(input)
+                if others
+                    .iter()
+                    .any(|other| controls.bindings(*other, *device).contains(input)) =>
+            {
+                Color::RED
+            }
+            _ => Color::BEIGE,
+        };
+    }
+
+    for mut text in conflict_query.iter_mut() {
+        text.sections[0].value = conflicts.join("\n");
+    }
+}
+
+fn input_name(input: &UserInput) -> String {
+    match input {
+        UserInput::Single(InputKind::Keyboard(key)) => format!("{:?}", key),
+        UserInput::Single(InputKind::GamepadButton(button)) => format!("{:?}", button),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_bindings_gain_new_actions() {
+        let mut saved = Controls::default();
+        saved.0.clear_action(Action::PinWaypoint);
+        saved.0.clear_action(Action::ToggleAutopilot);
+        // The player moved something else onto the autopilot's default key
+        saved.0.insert(KeyCode::P, Action::Boost);
+
+        let merged = saved.with_defaults();
+
+        assert_eq!(
+            merged.bindings(Action::PinWaypoint, Device::Keyboard),
+            vec![UserInput::from(KeyCode::T)]
+        );
+        assert!(merged
+            .bindings(Action::ToggleAutopilot, Device::Keyboard)
+            .is_empty());
+    }
+
+    #[test]
+    fn rebind_replaces_one_slot() {
+        let mut controls = Controls::default();
+
+        controls.rebind(Action::Forward, Device::Keyboard, 0, KeyCode::I.into());
+        assert_eq!(
+            controls.bindings(Action::Forward, Device::Keyboard),
+            vec![UserInput::from(KeyCode::I), UserInput::from(KeyCode::Up)]
+        );
+
+        controls.rebind(
+            Action::Boost,
+            Device::Keyboard,
+            1,
+            KeyCode::ShiftRight.into(),
+        );
+        assert_eq!(
+            controls.bindings(Action::Boost, Device::Keyboard),
+            vec![
+                UserInput::from(KeyCode::ShiftLeft),
+                UserInput::from(KeyCode::ShiftRight)
+            ]
+        );
+        // Mouse and gamepad bindings are left alone
+        assert!(controls
+            .0
+            .get(Action::Forward)
+            .contains(&UserInput::from(MouseButton::Left)));
+    }
+}
@@ -1,21 +1,46 @@
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
 
 use crate::{
     layer,
+    scanner::{ScanState, Scanner},
     util::{self, Edge},
-    Fonts, GameState, Player,
+    Action, Fonts, GameState, MovementSet, Player, Rotation,
 };
 
 pub struct DirectionIndicatorPlugin;
 impl Plugin for DirectionIndicatorPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<Waypoint>();
+
+        app.add_systems(OnEnter(GameState::Playing), clear_waypoint);
         app.add_systems(
             Update,
-            (update.after(crate::movement), decorate).run_if(in_state(GameState::Playing)),
+            (
+                pin,
+                update.after(MovementSet).after(pin),
+                decorate,
+                despawn_orphans,
+            )
+                .run_if(in_state(GameState::Playing)),
         );
     }
 }
 
+/// Distance in pixels between indicators and the edge of the screen
+const INDICATOR_MARGIN: f32 = 15.;
+/// Indicators closer together than this on screen, in pixels, are drawn as one
+const CLUSTER_RADIUS: f32 = 40.;
+/// Indicators start fading out past this distance from the player
+const FADE_START: f32 = 1000.;
+/// Indicators are at their faintest past this distance from the player
+const FADE_END: f32 = 5000.;
+const MIN_ALPHA: f32 = 0.3;
+/// How far off the ship's heading a target can be, in radians, for pinning
+const PIN_ANGLE: f32 = 0.35;
+/// The pinned indicator is drawn larger than the rest
+const PINNED_SCALE: f32 = 1.3;
+
 #[derive(Component)]
 pub struct DirectionIndicator {
     pub target: Entity,
@@ -28,6 +53,11 @@ pub struct DirectionIndicatorSettings {
     pub label: Option<String>,
 }
 
+/// The indicator target the player has pinned, if any. Its indicator is never
+/// faded or hidden in a cluster.
+#[derive(Resource, Default)]
+pub struct Waypoint(pub Option<Entity>);
+
 #[derive(Component)]
 struct DirectionIndicatorLabel;
 
@@ -37,8 +67,55 @@ struct DirectionIndicatorDistanceLabel;
 #[derive(Component)]
 struct DirectionIndicatorArrow;
 
+/// Where an off-screen indicator wants to be drawn
+struct Placement {
+    entity: Entity,
+    diff: Vec2,
+    edge: (Vec2, Edge),
+    distance: f32,
+    pinned: bool,
+    /// Other indicators drawn as part of this one
+    clustered: usize,
+}
+
+fn clear_waypoint(mut waypoint: ResMut<Waypoint>) {
+    waypoint.0 = None;
+}
+
+/// Pins the indicator closest to where the ship is pointing, or unpins it if
+/// it was already pinned
+fn pin(
+    player_query: Query<(&ActionState<Action>, &Transform, &Rotation), With<Player>>,
+    indicator_query: Query<&DirectionIndicator>,
+    target_query: Query<&Transform>,
+    mut waypoint: ResMut<Waypoint>,
+) {
+    let (action_state, player, rotation) = player_query.single();
+
+    if !action_state.just_pressed(Action::PinWaypoint) {
+        return;
+    }
+
+    let heading = Vec2::from_angle(rotation.0);
+
+    let nearest = indicator_query
+        .iter()
+        .filter_map(|indicator| {
+            let target = target_query.get(indicator.target).ok()?;
+            let diff = (target.translation - player.translation).truncate();
+            let angle = heading.angle_between(diff).abs();
+
+            (angle <= PIN_ANGLE).then_some((indicator.target, angle))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(target, _)| target);
+
+    waypoint.0 = if nearest == waypoint.0 { None } else { nearest };
+}
+
 fn update(
     mut query: Query<(
+        Entity,
         &DirectionIndicator,
         &mut Transform,
         &mut Visibility,
@@ -63,8 +140,18 @@ fn update(
             Without<DirectionIndicatorDistanceLabel>,
         ),
     >,
+    camera_query: Query<
+        (&Transform, &OrthographicProjection),
+        (
+            With<Camera>,
+            Without<DirectionIndicator>,
+            Without<DirectionIndicatorArrow>,
+            Without<DirectionIndicatorLabel>,
+            Without<DirectionIndicatorDistanceLabel>,
+        ),
+    >,
     mut label_query: Query<
-        &mut Transform,
+        (&mut Text, &mut Transform),
         (
             With<DirectionIndicatorLabel>,
             Without<DirectionIndicator>,
@@ -82,7 +169,7 @@ fn update(
         ),
     >,
     mut arrow_query: Query<
-        &mut Transform,
+        (&Handle<ColorMaterial>, &mut Transform),
         (
             With<DirectionIndicatorArrow>,
             Without<DirectionIndicator>,
@@ -90,18 +177,27 @@ fn update(
             Without<DirectionIndicatorDistanceLabel>,
         ),
     >,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    waypoint: Res<Waypoint>,
 ) {
     let player = player_query.single();
+    let (camera, projection) = camera_query.single();
+
+    // Indicators sit just inside the edge of the view, so they follow the
+    // camera and grow with its zoom to stay the same size on screen. The
+    // projection's area tracks the window size, so this holds at any
+    // resolution.
+    let on_screen_rect = projection.area.half_size();
+    let indicator_rect = on_screen_rect - Vec2::splat(INDICATOR_MARGIN) * projection.scale;
 
-    let indicator_rect = Vec2::new(625., 345.);
-    let on_screen_rect = Vec2::new(640., 360.);
+    let mut placements = Vec::new();
 
-    for (indicator, mut transform, mut visibility, children) in query.iter_mut() {
+    for (entity, indicator, _, mut visibility, _) in query.iter_mut() {
         let Ok(target_transform) = transform_query.get(indicator.target) else {
             continue;
         };
 
-        let diff = target_transform.translation.truncate() - player.translation.truncate();
+        let diff = target_transform.translation.truncate() - camera.translation.truncate();
 
         // TODO this should be a proper collision with the object geometry and the screen.
         if util::point_in_rect(diff, -on_screen_rect, on_screen_rect) {
@@ -109,30 +205,85 @@ fn update(
             continue;
         }
 
-        let projection =
-            util::project_onto_bounding_rectangle(diff, -indicator_rect, indicator_rect).unwrap();
+        placements.push(Placement {
+            entity,
+            diff,
+            edge: util::project_onto_bounding_rectangle(diff, -indicator_rect, indicator_rect)
+                .unwrap(),
+            distance: target_transform
+                .translation
+                .truncate()
+                .distance(player.translation.truncate()),
+            pinned: waypoint.0 == Some(indicator.target),
+            clustered: 0,
+        });
+    }
+
+    // The pinned indicator leads any cluster it's in, then the nearest target
+    placements.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(a.distance.total_cmp(&b.distance))
+    });
+
+    let cluster_radius = CLUSTER_RADIUS * projection.scale;
+    let mut leaders: Vec<Placement> = Vec::new();
+
+    for placement in placements {
+        let leader = leaders
+            .iter_mut()
+            .find(|leader| leader.edge.0.distance(placement.edge.0) < cluster_radius);
+
+        if let Some(leader) = leader {
+            leader.clustered += 1;
+
+            if let Ok((_, _, _, mut visibility, _)) = query.get_mut(placement.entity) {
+                *visibility = Visibility::Hidden;
+            }
+        } else {
+            leaders.push(placement);
+        }
+    }
+
+    for placement in leaders {
+        let Ok((_, indicator, mut transform, mut visibility, children)) =
+            query.get_mut(placement.entity)
+        else {
+            continue;
+        };
 
-        let pos = projection.0 + player.translation.truncate();
+        let pos = placement.edge.0 + camera.translation.truncate();
 
         transform.translation.x = pos.x;
         transform.translation.y = pos.y;
+        let scale = projection.scale * if placement.pinned { PINNED_SCALE } else { 1. };
+        transform.scale = Vec3::new(scale, scale, 1.);
+
+        let alpha = if placement.pinned {
+            1.
+        } else {
+            let fade = (placement.distance - FADE_START) / (FADE_END - FADE_START);
+            1. - fade.clamp(0., 1.) * (1. - MIN_ALPHA)
+        };
+        let color = indicator.settings.color.with_a(alpha);
 
-        let theta = diff.y.atan2(diff.x);
+        let theta = placement.diff.y.atan2(placement.diff.x);
         let theta_label = theta + std::f32::consts::PI;
         let sin_cos = theta_label.sin_cos();
 
-        let offset = match (projection.1, indicator.settings.label.as_ref()) {
+        let offset = match (placement.edge.1, indicator.settings.label.as_ref()) {
             (Edge::Top, Some(_)) => Vec2::new(0., -6.),
             (_, Some(_)) => Vec2::new(0., 6.),
             _ => Vec2::ZERO,
         };
 
         for child in children {
-            if let Ok(mut label_transform) = label_query.get_mut(*child) {
+            if let Ok((mut label, mut label_transform)) = label_query.get_mut(*child) {
                 let pos = Vec2::new(sin_cos.1, sin_cos.0) * 30. + offset;
 
                 label_transform.translation.x = pos.x;
                 label_transform.translation.y = pos.y;
+                label.sections[0].style.color = color;
 
                 continue;
             }
@@ -143,11 +294,25 @@ fn update(
                 label_transform.translation.x = pos.x;
                 label_transform.translation.y = pos.y;
 
-                label.sections[0].value = format!("{:.1}Mm", diff.length() / 1000.);
+                let mut value = format!("{:.1}Mm", placement.distance / 1000.);
+                if placement.clustered > 0 {
+                    value += &format!(" +{}", placement.clustered);
+                }
+                label.sections[0].value = value;
+                label.sections[0].style.color = color;
             }
 
-            if let Ok(mut arrow) = arrow_query.get_mut(*child) {
+            if let Ok((material, mut arrow)) = arrow_query.get_mut(*child) {
                 arrow.rotation = Quat::from_rotation_z(theta + std::f32::consts::FRAC_PI_2);
+
+                if materials
+                    .get(material)
+                    .is_some_and(|material| material.color != color)
+                {
+                    if let Some(material) = materials.get_mut(material) {
+                        material.color = color;
+                    }
+                }
                 continue;
             }
         }
@@ -219,3 +384,25 @@ fn decorate(
             .push_children(&[arrow, text, distance_text]);
     }
 }
+
+/// Removes indicators whose target no longer exists, e.g. a destroyed enemy, or
+/// has been collected
+fn despawn_orphans(
+    mut commands: Commands,
+    query: Query<(Entity, &DirectionIndicator)>,
+    target_query: Query<()>,
+    scanner: Res<Scanner>,
+    mut waypoint: ResMut<Waypoint>,
+) {
+    for (entity, indicator) in query.iter() {
+        if target_query.get(indicator.target).is_err()
+            || scanner.state(indicator.target) == Some(ScanState::Collected)
+        {
+            commands.entity(entity).despawn_recursive();
+
+            if waypoint.0 == Some(indicator.target) {
+                waypoint.0 = None;
+            }
+        }
+    }
+}
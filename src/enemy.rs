@@ -1,12 +1,21 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_spatial::{kdtree::KDTree2, SpatialAccess};
-use rand::{thread_rng, Rng};
+use bevy_spatial::{kdtree::KDTree2, point::Point2, SpatialAccess};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
-    fuel::SpawnFuelPelletEvent, util, DespawnOnRestart, GameState, Health, MaxVelocity, Player,
-    SpatialIndex, Velocity,
+    camera::MAX_LOOK_AHEAD,
+    combat_feedback::{DeathEvent, HitFlash},
+    fuel::SpawnFuelPelletEvent,
+    gravity::{AffectedByGravity, Gravity},
+    physics::Interpolated,
+    run::RunSeed,
+    shield::DamageEvent,
+    system_event::Pirate,
+    util, DespawnOnRestart, GameState, Health, MaxVelocity, PhysicsSet, Player, SpatialIndex,
+    Velocity,
 };
 #[derive(Resource)]
 struct RampUpTimer(Timer);
@@ -17,6 +26,10 @@ impl Default for SpawnTimer {
         Self(Timer::from_seconds(2.0, TimerMode::Repeating))
     }
 }
+/// Picks where enemies spawn. Reseeded for each system, so that a run plays
+/// out the same way when replayed.
+#[derive(Resource)]
+struct SpawnRng(ChaCha8Rng);
 #[derive(Resource, Deref)]
 struct MaxEnemies(usize);
 impl Default for MaxEnemies {
@@ -28,46 +41,82 @@ impl Default for MaxEnemies {
 #[derive(Component)]
 pub struct Enemy;
 
+const ENEMY_STEERING: f32 = 2.;
+/// Distance in pixels beyond the edge of the screen that enemies spawn at
+const SPAWN_MARGIN: f32 = 50.;
+/// How far past the corners of the spawn area enemies get before they are
+/// removed
+const DESPAWN_MARGIN: f32 = 100.;
+/// Damage dealt to the player by an enemy flying into them
+const RAM_DAMAGE: f32 = 10.;
+
 pub struct EnemyPlugin;
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(MaxEnemies::default())
             .insert_resource(SpawnTimer::default())
-            .insert_resource(RampUpTimer(Timer::from_seconds(30., TimerMode::Repeating)));
+            .insert_resource(SpawnRng(ChaCha8Rng::seed_from_u64(0)))
+            .insert_resource(RampUpTimer(Timer::from_seconds(30., TimerMode::Repeating)))
+            .init_resource::<KDTree2<SpatialIndex>>();
 
         app.add_systems(
-            Update,
-            (spawn_enemy, move_enemy, ramp_up, despawn).run_if(in_state(GameState::Playing)),
+            FixedUpdate,
+            (
+                index,
+                move_enemy.after(index).before(crate::movement),
+                spawn_enemy,
+                ramp_up,
+                (ram, despawn).after(crate::movement),
+            )
+                .run_if(in_state(GameState::Playing))
+                .in_set(PhysicsSet),
         );
 
+        app.add_systems(OnEnter(GameState::Playing), reseed);
         app.add_systems(OnExit(GameState::Warping), reset_timers);
+        app.add_systems(OnExit(GameState::Restarting), reset_timers);
     }
 }
 
+/// Rebuilds the enemies' spatial index from where the last physics step left
+/// them, so that steering doesn't depend on the frame rate
+fn index(
+    mut tree: ResMut<KDTree2<SpatialIndex>>,
+    query: Query<(Entity, &Transform), With<SpatialIndex>>,
+) {
+    let points: Vec<Point2> = query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()).into())
+        .collect();
+
+    tree.tree = kd_tree::KdTree::build_by_ordered_float(points);
+}
+
 fn spawn_enemy(
     mut commands: Commands,
     mut timer: ResMut<SpawnTimer>,
+    mut rng: ResMut<SpawnRng>,
     max: Res<MaxEnemies>,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     player_query: Query<&Transform, With<Player>>,
+    projection_query: Query<&OrthographicProjection>,
     enemy_query: Query<(), With<Enemy>>,
 ) {
-    timer.0.tick(time.delta());
+    timer.0.tick(fixed_time.period);
     if !timer.0.just_finished() {
         return;
     }
 
     let player = player_query.single();
+    let projection = projection_query.single();
 
     let enemies = enemy_query.iter().len();
     if enemies > **max {
         return;
     }
 
-    // TODO hardcoded based on screen size
-    let spawn_bounds = Vec2::new(700., 410.);
-
-    let theta = thread_rng().gen_range(0.0..std::f32::consts::TAU);
+    let spawn_bounds = spawn_bounds(projection);
+    let theta = rng.0.gen_range(0.0..std::f32::consts::TAU);
 
     let pos =
         util::project_onto_bounding_rectangle(Vec2::from_angle(theta), -spawn_bounds, spawn_bounds)
@@ -75,29 +124,57 @@ fn spawn_enemy(
             .0
             + player.translation.truncate();
 
-    commands.spawn((
+    commands.spawn(enemy_bundle(pos, Color::PURPLE, 1.));
+}
+
+/// Half the size of the rectangle around the player that enemies spawn on.
+///
+/// Just out of view, whatever the window size and zoom. Centred on the player
+/// rather than the smoothed and shaken camera, allowing for how far the camera
+/// can look ahead of them.
+fn spawn_bounds(projection: &OrthographicProjection) -> Vec2 {
+    projection.area.half_size()
+        + Vec2::splat(SPAWN_MARGIN) * projection.scale
+        + Vec2::splat(MAX_LOOK_AHEAD)
+}
+
+/// Enemies further than this from the player are removed. Always outside the
+/// spawn area, so that new enemies aren't removed as soon as they arrive.
+fn despawn_distance(projection: &OrthographicProjection) -> f32 {
+    spawn_bounds(projection).length() + DESPAWN_MARGIN
+}
+
+pub fn enemy_bundle(pos: Vec2, color: Color, health: f32) -> impl Bundle {
+    let transform = Transform::from_translation(pos.extend(crate::layer::SHIP));
+
+    (
         SpriteBundle {
-            transform: Transform::from_translation(pos.extend(crate::layer::SHIP)),
+            transform,
             sprite: Sprite {
-                color: Color::PURPLE,
+                color,
                 custom_size: Some(Vec2::new(20., 20.)),
                 ..default()
             },
             ..default()
         },
         Enemy,
+        HitFlash::new(color),
         Health {
-            current: 1.,
-            max: 1.,
+            current: health,
+            max: health,
         },
         MaxVelocity(30.),
         Velocity::default(),
         SpatialIndex,
+        Interpolated::new(&transform),
+        AffectedByGravity,
         DespawnOnRestart,
-    ));
+    )
 }
 
 fn move_enemy(
+    fixed_time: Res<FixedTime>,
+    gravity: Res<Gravity>,
     player_query: Query<&Transform, With<Player>>,
     mut enemy_query: Query<(&mut Velocity, &MaxVelocity, &Transform), With<Enemy>>,
     tree: Res<KDTree2<SpatialIndex>>,
@@ -122,12 +199,27 @@ fn move_enemy(
             dir += -diff.normalize();
         };
 
-        velocity.0 = dir * max_velocity.0;
+        let desired = dir * max_velocity.0;
+
+        // With gravity on, steer towards the desired velocity rather than
+        // snapping to it, so that gravity wells can pull enemies off course.
+        velocity.0 = if gravity.enabled {
+            velocity.0.lerp(
+                desired,
+                (ENEMY_STEERING * fixed_time.period.as_secs_f32()).min(1.),
+            )
+        } else {
+            desired
+        };
     }
 }
 
-fn ramp_up(time: Res<Time>, mut spawn: ResMut<SpawnTimer>, mut ramp: ResMut<RampUpTimer>) {
-    ramp.0.tick(time.delta());
+fn ramp_up(
+    fixed_time: Res<FixedTime>,
+    mut spawn: ResMut<SpawnTimer>,
+    mut ramp: ResMut<RampUpTimer>,
+) {
+    ramp.0.tick(fixed_time.period);
     if !ramp.0.just_finished() {
         return;
     }
@@ -138,29 +230,79 @@ fn ramp_up(time: Res<Time>, mut spawn: ResMut<SpawnTimer>, mut ramp: ResMut<Ramp
 
 fn despawn(
     mut commands: Commands,
-    query: Query<(Entity, &Health, &Transform), With<Enemy>>,
+    query: Query<(Entity, &Health, &Transform, &HitFlash, Option<&Pirate>), With<Enemy>>,
     player_query: Query<&Transform, With<Player>>,
+    projection_query: Query<&OrthographicProjection>,
     mut events: EventWriter<SpawnFuelPelletEvent>,
+    mut death_events: EventWriter<DeathEvent>,
 ) {
     let player = player_query.single();
+    let despawn_distance = despawn_distance(projection_query.single());
 
-    for (entity, health, transform) in query.iter() {
-        if health.current < health.max {
+    for (entity, health, transform, flash, pirate) in query.iter() {
+        if health.current <= 0. {
             commands.entity(entity).despawn();
             events.send(SpawnFuelPelletEvent {
                 location: transform.translation.truncate(),
             });
+            // Burst in the enemy's own colour rather than its hit flash
+            death_events.send(DeathEvent {
+                position: transform.translation.truncate(),
+                color: flash.color,
+            });
             continue;
         }
-        let dist =
-            (transform.translation.truncate() - player.translation.truncate()).length_squared();
-        if dist > 490_000.0 {
+
+        // Pirates chase the player however far they go, so that an ambush
+        // can't be ended by flying away from it
+        if pirate.is_some() {
+            continue;
+        }
+
+        let dist = transform
+            .translation
+            .truncate()
+            .distance(player.translation.truncate());
+        if dist > despawn_distance {
             commands.entity(entity).despawn();
             continue;
         }
     }
 }
 
+/// Enemies that reach the player crash into them, destroying themselves
+fn ram(
+    mut commands: Commands,
+    query: Query<(Entity, &Health, &Transform), With<Enemy>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut events: EventWriter<DamageEvent>,
+) {
+    let (player_entity, player) = player_query.single();
+
+    for (entity, health, transform) in query.iter() {
+        // Already shot down, and despawned elsewhere
+        if health.current <= 0. {
+            continue;
+        }
+
+        let dist = transform
+            .translation
+            .truncate()
+            .distance(player.translation.truncate());
+        if dist < 20. {
+            commands.entity(entity).despawn();
+            events.send(DamageEvent {
+                entity: player_entity,
+                amount: RAM_DAMAGE,
+            });
+        }
+    }
+}
+
+fn reseed(mut rng: ResMut<SpawnRng>, seed: Res<RunSeed>) {
+    rng.0 = seed.rng("enemy");
+}
+
 fn reset_timers(mut commands: Commands, mut ramp: ResMut<RampUpTimer>) {
     commands.insert_resource(SpawnTimer::default());
 
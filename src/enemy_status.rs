@@ -0,0 +1,276 @@
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    direction_indicator::{DirectionIndicator, Waypoint},
+    enemy::Enemy,
+    layer,
+    system_event::Pirate,
+    util, Action, DespawnOnRestart, GameState, Health, MovementSet, Player,
+};
+
+pub struct EnemyStatusPlugin;
+impl Plugin for EnemyStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyStatusSettings>();
+
+        app.add_systems(
+            Update,
+            (toggle, update.after(toggle).after(MovementSet)).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Most health bars shown at once. Bars are only drawn for damaged enemies
+/// in view, so this is rarely reached.
+const MAX_HEALTH_BARS: usize = 100;
+const MAX_STATUS_ICONS: usize = 100;
+const BAR_SIZE: Vec2 = Vec2::new(24., 3.);
+/// Height of the bar above the enemy's centre
+const BAR_OFFSET: f32 = 16.;
+const ICON_SIZE: f32 = 5.;
+const ICON_SPACING: f32 = 8.;
+/// Height of the icons above the enemy's centre
+const ICON_OFFSET: f32 = 23.;
+/// Enemies this far outside the view, in pixels, still get their overlays so
+/// that they don't pop in at the edge
+const VIEW_MARGIN: f32 = 30.;
+
+/// Whether health bars and status icons are drawn over enemies
+#[derive(Resource)]
+pub struct EnemyStatusSettings {
+    pub visible: bool,
+}
+
+impl Default for EnemyStatusSettings {
+    fn default() -> Self {
+        Self { visible: true }
+    }
+}
+
+/// Something worth knowing about an enemy at a glance
+#[derive(Clone, Copy)]
+enum Status {
+    /// The player's waypoint
+    Pinned,
+    /// Revealed by the scanner in enemy mode
+    Tracked,
+    /// Part of a pirate ambush that has to be destroyed
+    Pirate,
+}
+
+impl Status {
+    fn color(self) -> Color {
+        match self {
+            Self::Pinned => Color::GOLD,
+            Self::Tracked => Color::VIOLET,
+            Self::Pirate => Color::CRIMSON,
+        }
+    }
+}
+
+/// The bar's background. Hidden when not in use.
+#[derive(Component)]
+struct HealthBar;
+#[derive(Component)]
+struct HealthBarFill;
+/// Hidden when not in use
+#[derive(Component)]
+struct StatusIcon;
+
+fn toggle(
+    query: Query<&ActionState<Action>, With<Player>>,
+    mut settings: ResMut<EnemyStatusSettings>,
+) {
+    let action_state = query.single();
+
+    if action_state.just_pressed(Action::ToggleEnemyStatus) {
+        settings.visible = !settings.visible;
+    }
+}
+
+fn fill_color(fraction: f32) -> Color {
+    if fraction > 0.5 {
+        Color::GREEN
+    } else if fraction > 0.25 {
+        Color::YELLOW
+    } else {
+        Color::RED
+    }
+}
+
+/// Places health bars and icons over enemies in view, reusing the same
+/// sprites every frame rather than giving each enemy its own
+fn update(
+    mut commands: Commands,
+    settings: Res<EnemyStatusSettings>,
+    waypoint: Res<Waypoint>,
+    camera_query: Query<(&Transform, &OrthographicProjection), (With<Camera>, Without<HealthBar>)>,
+    enemy_query: Query<
+        (Entity, &Transform, &Health, Option<&Pirate>),
+        (With<Enemy>, Without<HealthBar>),
+    >,
+    indicator_query: Query<&DirectionIndicator>,
+    mut bar_query: Query<
+        (&mut Transform, &mut Visibility, &Children),
+        (
+            With<HealthBar>,
+            Without<HealthBarFill>,
+            Without<StatusIcon>,
+            Without<Enemy>,
+            Without<Camera>,
+        ),
+    >,
+    mut fill_query: Query<
+        (&mut Transform, &mut Sprite),
+        (
+            With<HealthBarFill>,
+            Without<HealthBar>,
+            Without<StatusIcon>,
+            Without<Enemy>,
+            Without<Camera>,
+        ),
+    >,
+    mut icon_query: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        (
+            With<StatusIcon>,
+            Without<HealthBar>,
+            Without<HealthBarFill>,
+            Without<Enemy>,
+            Without<Camera>,
+        ),
+    >,
+) {
+    let mut bars = Vec::new();
+    let mut icons = Vec::new();
+
+    if settings.visible {
+        let (camera, projection) = camera_query.single();
+        let camera_pos = camera.translation.truncate();
+        let bounds = projection.area.half_size() + Vec2::splat(VIEW_MARGIN) * projection.scale;
+
+        let tracked: HashSet<Entity> = indicator_query.iter().map(|i| i.target).collect();
+
+        for (entity, transform, health, pirate) in enemy_query.iter() {
+            let pos = transform.translation.truncate();
+            if !util::point_in_rect(pos - camera_pos, -bounds, bounds) {
+                continue;
+            }
+
+            if health.current < health.max {
+                bars.push((pos, (health.current / health.max).clamp(0., 1.)));
+            }
+
+            let statuses: Vec<Status> = [
+                (waypoint.0 == Some(entity)).then_some(Status::Pinned),
+                tracked.contains(&entity).then_some(Status::Tracked),
+                pirate.map(|_| Status::Pirate),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+
+            // A centred row above the enemy
+            let start = -(statuses.len() as f32 - 1.) / 2. * ICON_SPACING;
+            for (i, status) in statuses.into_iter().enumerate() {
+                let offset = Vec2::new(start + i as f32 * ICON_SPACING, ICON_OFFSET);
+                icons.push((pos + offset, status.color()));
+            }
+        }
+    }
+
+    let mut bars = bars.into_iter();
+    let mut bar_count = 0;
+
+    for (mut transform, mut visibility, children) in bar_query.iter_mut() {
+        bar_count += 1;
+
+        let Some((pos, fraction)) = bars.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        transform.translation = (pos + Vec2::new(0., BAR_OFFSET)).extend(layer::EFFECT);
+        *visibility = Visibility::Visible;
+
+        for child in children {
+            if let Ok((mut fill_transform, mut sprite)) = fill_query.get_mut(*child) {
+                fill_transform.scale.x = fraction;
+                fill_transform.translation.x = -BAR_SIZE.x * (1. - fraction) / 2.;
+                sprite.color = fill_color(fraction);
+            }
+        }
+    }
+
+    for (pos, fraction) in bars.take(MAX_HEALTH_BARS.saturating_sub(bar_count)) {
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(0., 0., 0., 0.6),
+                        custom_size: Some(BAR_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        (pos + Vec2::new(0., BAR_OFFSET)).extend(layer::EFFECT),
+                    ),
+                    ..default()
+                },
+                HealthBar,
+                DespawnOnRestart,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: fill_color(fraction),
+                            custom_size: Some(BAR_SIZE),
+                            ..default()
+                        },
+                        transform: Transform {
+                            translation: Vec3::new(-BAR_SIZE.x * (1. - fraction) / 2., 0., 0.01),
+                            scale: Vec3::new(fraction, 1., 1.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    HealthBarFill,
+                ));
+            });
+    }
+
+    let mut icons = icons.into_iter();
+    let mut icon_count = 0;
+
+    for (mut transform, mut sprite, mut visibility) in icon_query.iter_mut() {
+        icon_count += 1;
+
+        let Some((pos, color)) = icons.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        transform.translation = pos.extend(layer::EFFECT);
+        sprite.color = color;
+        *visibility = Visibility::Visible;
+    }
+
+    for (pos, color) in icons.take(MAX_STATUS_ICONS.saturating_sub(icon_count)) {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(ICON_SIZE)),
+                    ..default()
+                },
+                // Diamonds, to stand out from the square enemies
+                transform: Transform::from_translation(pos.extend(layer::EFFECT))
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..default()
+            },
+            StatusIcon,
+            DespawnOnRestart,
+        ));
+    }
+}
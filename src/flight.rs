@@ -0,0 +1,302 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    direction_indicator::Waypoint, Acceleration, Action, AngularVelocity, GameState, MaxVelocity,
+    PhysicsSet, Player, Rotation, Throttle, Thrust, ThrusterStatus, Velocity,
+};
+
+pub struct FlightPlugin;
+impl Plugin for FlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (input, autopilot.before(crate::thruster))
+                .after(crate::player_input)
+                .run_if(in_state(GameState::Playing)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (
+                strafe.before(crate::apply_acceleration),
+                boost.after(strafe).before(crate::apply_acceleration),
+                dampers.after(crate::apply_acceleration),
+            )
+                .after(crate::acceleration)
+                .before(crate::movement)
+                .run_if(in_state(GameState::Playing))
+                .in_set(PhysicsSet),
+        );
+    }
+}
+
+/// Fraction of the main thrust available to the lateral thrusters
+const STRAFE_THRUST: f32 = 0.6;
+const BOOST_THRUST: f32 = 2.5;
+const BOOST_MAX_VELOCITY: f32 = 1.75;
+/// Energy used per second of boosting
+const BOOST_DRAIN: f32 = 35.;
+/// Energy regained per second while not boosting
+const BOOST_RECHARGE: f32 = 10.;
+/// Energy needed to start boosting again once it has stopped, so that holding
+/// boost with an empty pool doesn't flicker on and off as it recharges
+const BOOST_MIN_ENERGY: f32 = 20.;
+/// Fraction of the main thrust the autopilot plans to brake with, leaving
+/// time to turn around
+const AUTOPILOT_BRAKING: f32 = 0.4;
+/// Radians either side of the wanted direction the autopilot will thrust in
+const AUTOPILOT_THRUST_ANGLE: f32 = 0.3;
+/// Velocity error that gets full throttle from the autopilot
+const AUTOPILOT_FULL_THROTTLE: f32 = 60.;
+/// Velocity error the autopilot ignores, coasting instead
+const AUTOPILOT_TOLERANCE: f32 = 5.;
+/// Distance from the waypoint at which the autopilot hands back control
+const AUTOPILOT_ARRIVAL: f32 = 20.;
+
+/// Lateral thrust, -1 for left and 1 for right
+#[derive(Component, Default)]
+pub struct Strafe(pub f32);
+
+/// Bleeds off velocity that the ship isn't thrusting towards
+#[derive(Component)]
+pub struct InertialDampers {
+    pub enabled: bool,
+    /// Fraction of the unwanted velocity removed per second
+    pub strength: f32,
+}
+
+/// Flies the ship to the pinned waypoint until it arrives or the player takes
+/// the controls
+#[derive(Component, Default)]
+pub struct Autopilot {
+    pub engaged: bool,
+}
+
+/// An afterburner running off its own energy pool
+#[derive(Component)]
+pub struct Boost {
+    pub energy: f32,
+    pub max_energy: f32,
+    /// Whether the player is holding boost
+    pub requested: bool,
+    /// Whether the boost is firing, which it only does while thrusting
+    pub active: bool,
+}
+
+impl Boost {
+    pub fn new(max_energy: f32) -> Self {
+        Self {
+            energy: max_energy,
+            max_energy,
+            requested: false,
+            active: false,
+        }
+    }
+
+    /// Multiplier applied to the ship's max velocity
+    pub fn max_velocity_multiplier(&self) -> f32 {
+        if self.active {
+            BOOST_MAX_VELOCITY
+        } else {
+            1.
+        }
+    }
+}
+
+fn input(
+    mut query: Query<
+        (
+            &ActionState<Action>,
+            &mut Strafe,
+            &mut InertialDampers,
+            &mut Boost,
+        ),
+        With<Player>,
+    >,
+) {
+    let (action_state, mut strafe, mut dampers, mut boost) = query.single_mut();
+
+    let mut new_strafe = 0.;
+
+    if action_state.pressed(Action::StrafeLeft) {
+        new_strafe -= 1.;
+    }
+
+    if action_state.pressed(Action::StrafeRight) {
+        new_strafe += 1.;
+    }
+
+    if strafe.0 != new_strafe {
+        strafe.0 = new_strafe;
+    }
+
+    if action_state.just_pressed(Action::ToggleDampers) {
+        dampers.enabled = !dampers.enabled;
+    }
+
+    let requested = action_state.pressed(Action::Boost);
+
+    if boost.requested != requested {
+        boost.requested = requested;
+    }
+}
+
+/// Steers and throttles towards the waypoint, overriding `player_input` while
+/// engaged
+fn autopilot(
+    waypoint: Res<Waypoint>,
+    target_query: Query<&Transform>,
+    mut query: Query<
+        (
+            &ActionState<Action>,
+            &Transform,
+            &Rotation,
+            &Velocity,
+            &Thrust,
+            &MaxVelocity,
+            &mut Autopilot,
+            &mut AngularVelocity,
+            &mut ThrusterStatus,
+            &mut Throttle,
+        ),
+        With<Player>,
+    >,
+) {
+    let (
+        action_state,
+        transform,
+        rotation,
+        velocity,
+        thrust,
+        max_velocity,
+        mut autopilot,
+        mut angular,
+        mut thruster_status,
+        mut throttle,
+    ) = query.single_mut();
+
+    if action_state.just_pressed(Action::ToggleAutopilot) {
+        autopilot.engaged = !autopilot.engaged && waypoint.0.is_some();
+    }
+
+    if !autopilot.engaged {
+        return;
+    }
+
+    // Any steering or thrust from the player takes back control
+    let manual = [
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Forward,
+        Action::Reverse,
+    ]
+    .into_iter()
+    .any(|action| action_state.pressed(action))
+        || action_state.clamped_value(Action::Turn) != 0.
+        || action_state
+            .clamped_axis_pair(Action::Aim)
+            .is_some_and(|axis| axis.xy() != Vec2::ZERO);
+
+    let target = waypoint
+        .0
+        .and_then(|entity| target_query.get(entity).ok())
+        .filter(|_| !manual);
+    let Some(target) = target else {
+        autopilot.engaged = false;
+        return;
+    };
+
+    let diff = (target.translation - transform.translation).truncate();
+    let distance = diff.length();
+
+    if distance < AUTOPILOT_ARRIVAL {
+        autopilot.engaged = false;
+        return;
+    }
+
+    // Head for the waypoint, slowing down in time to stop on it
+    let speed = (2. * thrust.0 * AUTOPILOT_BRAKING * distance)
+        .sqrt()
+        .min(max_velocity.0);
+    let correction = diff / distance * speed - velocity.0;
+
+    let (direction, thrusting) = if correction.length() > AUTOPILOT_TOLERANCE {
+        (correction, true)
+    } else {
+        (diff, false)
+    };
+
+    let heading = Vec2::from_angle(rotation.0);
+    let angle = heading.angle_between(direction);
+
+    angular.0 = (angle * crate::AIM_TURN_GAIN).clamp(-1., 1.);
+
+    if thrusting && angle.abs() < AUTOPILOT_THRUST_ANGLE {
+        *thruster_status = ThrusterStatus::Forward;
+        throttle.0 = (correction.length() / AUTOPILOT_FULL_THROTTLE).min(1.);
+    } else {
+        *thruster_status = ThrusterStatus::None;
+        throttle.0 = 0.;
+    }
+}
+
+fn strafe(mut query: Query<(&mut Acceleration, &Strafe, &Thrust, &Rotation)>) {
+    for (mut acceleration, strafe, thrust, rotation) in query.iter_mut() {
+        if strafe.0 == 0. {
+            continue;
+        }
+
+        // Rotating the heading a quarter turn clockwise points to the right
+        let right = Vec2::from_angle(rotation.0 - std::f32::consts::FRAC_PI_2);
+
+        acceleration.0 += right * strafe.0 * thrust.0 * STRAFE_THRUST;
+    }
+}
+
+fn boost(fixed_time: Res<FixedTime>, mut query: Query<(&mut Acceleration, &mut Boost)>) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (mut acceleration, mut boost) in query.iter_mut() {
+        let thrusting = acceleration.0 != Vec2::ZERO;
+        let charged = if boost.active {
+            boost.energy > 0.
+        } else {
+            boost.energy >= BOOST_MIN_ENERGY
+        };
+        let active = boost.requested && thrusting && charged;
+
+        if boost.active != active {
+            boost.active = active;
+        }
+
+        if active {
+            boost.energy = (boost.energy - BOOST_DRAIN * dt).max(0.);
+
+            acceleration.0 *= BOOST_THRUST;
+        } else if boost.energy < boost.max_energy {
+            boost.energy = (boost.energy + BOOST_RECHARGE * dt).min(boost.max_energy);
+        }
+    }
+}
+
+fn dampers(
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(&mut Velocity, &Acceleration, &InertialDampers)>,
+) {
+    for (mut velocity, acceleration, dampers) in query.iter_mut() {
+        if !dampers.enabled {
+            continue;
+        }
+
+        // Keep whatever the ship is actively thrusting towards
+        let wanted = match acceleration.0.try_normalize() {
+            Some(dir) => dir * velocity.0.dot(dir).max(0.),
+            None => Vec2::ZERO,
+        };
+        let unwanted = velocity.0 - wanted;
+
+        let bleed = (dampers.strength * fixed_time.period.as_secs_f32()).min(1.);
+
+        velocity.0 -= unwanted * bleed;
+    }
+}
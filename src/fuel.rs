@@ -1,22 +1,29 @@
 use bevy::prelude::*;
 
 use crate::{
-    layer, Acceleration, DespawnOnRestart, FuelTank, GameState, MaxVelocity, Player, Velocity,
+    gravity::{AffectedByGravity, Gravity},
+    layer,
+    physics::Interpolated,
+    Acceleration, DespawnOnRestart, FuelTank, GameState, MaxVelocity, PhysicsSet, Player, Velocity,
 };
 
 pub struct FuelPlugin;
 impl Plugin for FuelPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SpawnFuelPelletEvent>();
+        app.add_systems(Update, spawn.run_if(in_state(GameState::Playing)));
         app.add_systems(
-            Update,
-            (movement, spawn).run_if(in_state(GameState::Playing)),
+            FixedUpdate,
+            movement
+                .before(crate::movement)
+                .run_if(in_state(GameState::Playing))
+                .in_set(PhysicsSet),
         );
     }
 }
 
 #[derive(Component)]
-struct FuelPellet;
+pub struct FuelPellet;
 #[derive(Event)]
 pub struct SpawnFuelPelletEvent {
     pub location: Vec2,
@@ -29,6 +36,8 @@ fn spawn(
     mut events: EventReader<SpawnFuelPelletEvent>,
 ) {
     for event in events.iter() {
+        let transform = Transform::from_translation(event.location.extend(layer::OBJECT));
+
         commands.spawn((
             ColorMesh2dBundle {
                 mesh: meshes
@@ -41,13 +50,15 @@ fn spawn(
                     )
                     .into(),
                 material: materials.add(Color::GREEN.into()),
-                transform: Transform::from_translation(event.location.extend(layer::OBJECT)),
+                transform,
                 ..default()
             },
+            Interpolated::new(&transform),
             Velocity::default(),
             Acceleration::default(),
             MaxVelocity(300.),
             FuelPellet,
+            AffectedByGravity,
             DespawnOnRestart,
         ));
     }
@@ -55,6 +66,7 @@ fn spawn(
 
 fn movement(
     mut commands: Commands,
+    gravity: Res<Gravity>,
     mut player_query: Query<(&Transform, &mut FuelTank), With<Player>>,
     mut query: Query<(Entity, &mut Velocity, &Transform), With<FuelPellet>>,
 ) {
@@ -68,7 +80,7 @@ fn movement(
         // TODO Maybe try applying velocity directly proportional to square distance
         if dist <= 60. {
             acceleration.0 = diff.normalize() * 120.;
-        } else {
+        } else if !gravity.enabled {
             acceleration.0 = Vec2::ZERO;
         }
 
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{Action, GameState, MovementSet, PhysicsSet, Player, Velocity};
+
+pub struct GravityPlugin;
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Gravity>();
+        app.add_systems(
+            Update,
+            (toggle, trajectory_preview.after(MovementSet)).run_if(in_state(GameState::Playing)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            apply_gravity
+                .after(crate::apply_acceleration)
+                .before(crate::movement)
+                .run_if(in_state(GameState::Playing))
+                .in_set(PhysicsSet),
+        );
+    }
+}
+
+/// Keeps accelerations from blowing up when something passes right through
+/// the center of a body
+const SOFTENING_SQUARED: f32 = 2500.;
+const PREVIEW_SECONDS: f32 = 6.;
+const PREVIEW_STEPS: usize = 60;
+
+#[derive(Resource, Default)]
+pub struct Gravity {
+    pub enabled: bool,
+}
+
+/// The gravitational parameter (G * M) of a body that attracts things
+#[derive(Component)]
+pub struct Mass(pub f32);
+
+#[derive(Component)]
+pub struct AffectedByGravity;
+
+fn toggle(query: Query<&ActionState<Action>, With<Player>>, mut gravity: ResMut<Gravity>) {
+    let action_state = query.single();
+
+    if action_state.just_pressed(Action::ToggleGravity) {
+        gravity.enabled = !gravity.enabled;
+    }
+}
+
+/// The acceleration at `pos` due to all bodies with `Mass`
+fn acceleration_at(pos: Vec2, bodies: &[(Vec2, f32)]) -> Vec2 {
+    bodies
+        .iter()
+        .map(|(body, mass)| {
+            let diff = *body - pos;
+            let dist_squared = diff.length_squared() + SOFTENING_SQUARED;
+
+            diff.normalize_or_zero() * *mass / dist_squared
+        })
+        .sum()
+}
+
+fn bodies(query: &Query<(&Transform, &Mass)>) -> Vec<(Vec2, f32)> {
+    query
+        .iter()
+        .map(|(transform, mass)| (transform.translation.truncate(), mass.0))
+        .collect()
+}
+
+fn apply_gravity(
+    fixed_time: Res<FixedTime>,
+    gravity: Res<Gravity>,
+    body_query: Query<(&Transform, &Mass)>,
+    mut query: Query<(&Transform, &mut Velocity), With<AffectedByGravity>>,
+) {
+    if !gravity.enabled {
+        return;
+    }
+
+    let bodies = bodies(&body_query);
+
+    for (transform, mut velocity) in query.iter_mut() {
+        velocity.0 += acceleration_at(transform.translation.truncate(), &bodies)
+            * fixed_time.period.as_secs_f32();
+    }
+}
+
+/// Draws the path the player will follow if they stop thrusting
+fn trajectory_preview(
+    gravity: Res<Gravity>,
+    body_query: Query<(&Transform, &Mass)>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    mut gizmos: Gizmos,
+) {
+    if !gravity.enabled {
+        return;
+    }
+
+    let bodies = bodies(&body_query);
+
+    let (transform, velocity) = player_query.single();
+
+    let dt = PREVIEW_SECONDS / PREVIEW_STEPS as f32;
+
+    let mut pos = transform.translation.truncate();
+    let mut vel = velocity.0;
+
+    let points = std::iter::once(pos).chain((0..PREVIEW_STEPS).map(|_| {
+        vel += acceleration_at(pos, &bodies) * dt;
+        pos += vel * dt;
+        pos
+    }));
+
+    gizmos.linestrip_2d(points, Color::rgba(0.5, 0.8, 1.0, 0.5));
+}
@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    loadout::{module_index, Loadout, Slot, SlotKind},
+    profile::Profile,
+};
+
+pub struct HullPlugin;
+impl Plugin for HullPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Hulls::load());
+    }
+}
+
+/// The ship hulls defined in `assets/hulls.ron`
+#[derive(Resource)]
+pub struct Hulls(pub Vec<Hull>);
+
+impl Hulls {
+    fn load() -> Self {
+        // Built in, so that a missing file is caught at compile time. A broken
+        // one panics here at startup, which the test below catches first.
+        let hulls: Vec<Hull> =
+            ron::from_str(include_str!("../assets/hulls.ron")).expect("invalid hulls.ron");
+
+        assert!(!hulls.is_empty(), "hulls.ron defines no hulls");
+
+        for hull in &hulls {
+            for slot in &hull.slots {
+                if let Some(module) = &slot.module {
+                    assert!(
+                        module_index(module).is_some(),
+                        "hulls.ron: unknown module {:?} on {}",
+                        module,
+                        hull.name
+                    );
+                }
+            }
+        }
+
+        Self(hulls)
+    }
+}
+
+/// A ship the player can start a run with
+#[derive(Deserialize)]
+pub struct Hull {
+    pub name: String,
+    pub description: String,
+    pub thrust: f32,
+    pub max_velocity: f32,
+    pub rotation_speed: f32,
+    pub fuel_capacity: u32,
+    pub cargo_capacity: u32,
+    /// Hull strength, the ship's health
+    pub health: f32,
+    /// The hull's module slots and what comes installed in them
+    pub slots: Vec<HullSlot>,
+    pub shape: HullShape,
+    pub color: (f32, f32, f32),
+    /// Locked until the player's profile meets this, if given
+    #[serde(default)]
+    pub unlock: Option<Unlock>,
+}
+
+impl Hull {
+    pub fn color(&self) -> Color {
+        Color::rgb(self.color.0, self.color.1, self.color.2)
+    }
+
+    /// The loadout a new run starts with
+    pub fn loadout(&self) -> Loadout {
+        Loadout(
+            self.slots
+                .iter()
+                .map(|slot| Slot {
+                    kind: slot.kind,
+                    module: slot.module.as_deref().and_then(module_index),
+                })
+                .collect(),
+        )
+    }
+
+    /// Counts the hull's slots of each kind, for showing in menus
+    pub fn describe_slots(&self) -> String {
+        let mut counts: Vec<(SlotKind, usize)> = vec![];
+        for slot in &self.slots {
+            match counts.iter_mut().find(|(kind, _)| *kind == slot.kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((slot.kind, 1)),
+            }
+        }
+
+        counts
+            .iter()
+            .map(|(kind, count)| format!("{} {:?}", count, kind))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    pub fn unlocked(&self, profile: &Profile) -> bool {
+        self.unlock
+            .as_ref()
+            .is_none_or(|unlock| unlock.met(profile))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HullSlot {
+    pub kind: SlotKind,
+    /// Name of the module installed at the start of a run
+    #[serde(default)]
+    pub module: Option<String>,
+}
+
+/// The shape of a hull's mesh, pointing along the x axis
+#[derive(Deserialize)]
+pub enum HullShape {
+    Polygon { radius: f32, sides: usize },
+    Quad { length: f32, width: f32 },
+}
+
+impl HullShape {
+    pub fn mesh(&self) -> Mesh {
+        match *self {
+            Self::Polygon { radius, sides } => shape::RegularPolygon::new(radius, sides).into(),
+            Self::Quad { length, width } => shape::Quad::new(Vec2::new(length, width)).into(),
+        }
+    }
+
+    /// Rotation that points the mesh forwards
+    pub fn rotation(&self) -> Quat {
+        match self {
+            // Regular polygons start with a vertex pointing up
+            Self::Polygon { .. } => Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2),
+            Self::Quad { .. } => Quat::IDENTITY,
+        }
+    }
+
+    /// Distance from the center to the furthest point of the hull
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Polygon { radius, .. } => radius,
+            Self::Quad { length, width } => Vec2::new(length, width).length() / 2.,
+        }
+    }
+
+    /// Distance from the center to the back of the hull, where the thruster goes
+    pub fn rear(&self) -> f32 {
+        match *self {
+            Self::Polygon { radius, sides } => radius * (std::f32::consts::PI / sides as f32).cos(),
+            Self::Quad { length, .. } => length / 2.,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Unlock {
+    /// Reach this system, counting the first as 1
+    FurthestSystem(u32),
+    /// Hold this many credits at once
+    MostCredits(u32),
+}
+
+impl Unlock {
+    fn met(&self, profile: &Profile) -> bool {
+        match *self {
+            Self::FurthestSystem(system) => profile.furthest_system >= system,
+            Self::MostCredits(credits) => profile.most_credits >= credits,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::FurthestSystem(system) => format!("Reach system {}", system),
+            Self::MostCredits(credits) => format!("Hold {} credits", credits),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hulls_load() {
+        Hulls::load();
+    }
+}
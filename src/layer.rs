@@ -1,5 +1,6 @@
 pub const FADE: f32 = 21.;
 pub const UI: f32 = 20.;
+pub const EFFECT: f32 = 12.;
 pub const BULLET: f32 = 11.;
 pub const SHIP: f32 = 10.;
 pub const OBJECT: f32 = 2.0;
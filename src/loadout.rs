@@ -0,0 +1,497 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    basic_laser::BasicLaser,
+    commodity::CargoHold,
+    scanner::{ScannerModule, ScannerStats},
+    shield::Shield,
+    FuelTank, GameState, MaxVelocity, Thrust,
+};
+
+pub struct LoadoutPlugin;
+impl Plugin for LoadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply.run_if(in_state(GameState::Playing).or_else(in_state(GameState::Restarting))),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum SlotKind {
+    Weapon,
+    Engine,
+    Scanner,
+    FuelTank,
+    Cargo,
+    Shield,
+}
+
+/// What a module does once installed
+pub enum ModuleEffect {
+    Laser {
+        cooldown: f32,
+        damage: f32,
+    },
+    /// Multipliers applied to the hull's thrust and max velocity
+    Engine {
+        thrust: f32,
+        max_velocity: f32,
+    },
+    Scanner(ScannerStats),
+    /// Extra fuel capacity
+    FuelTank(u32),
+    /// Extra cargo capacity
+    Cargo(u32),
+    /// Shield points, and how many are regained per second
+    Shield {
+        capacity: f32,
+        recharge: f32,
+    },
+}
+
+pub struct ModuleStats {
+    pub name: &'static str,
+    pub slot: SlotKind,
+    /// Price in credits when buying this module at a planet
+    pub cost: u32,
+    pub effect: ModuleEffect,
+}
+
+pub const MODULES: [ModuleStats; 12] = [
+    ModuleStats {
+        name: "Basic Laser",
+        slot: SlotKind::Weapon,
+        cost: 60,
+        effect: ModuleEffect::Laser {
+            cooldown: 1.,
+            damage: 1.,
+        },
+    },
+    ModuleStats {
+        name: "Rapid Laser",
+        slot: SlotKind::Weapon,
+        cost: 150,
+        effect: ModuleEffect::Laser {
+            cooldown: 0.5,
+            damage: 0.75,
+        },
+    },
+    ModuleStats {
+        name: "Heavy Laser",
+        slot: SlotKind::Weapon,
+        cost: 250,
+        effect: ModuleEffect::Laser {
+            cooldown: 1.5,
+            damage: 3.,
+        },
+    },
+    ModuleStats {
+        name: "Ion Engine",
+        slot: SlotKind::Engine,
+        cost: 120,
+        effect: ModuleEffect::Engine {
+            thrust: 1.25,
+            max_velocity: 1.1,
+        },
+    },
+    ModuleStats {
+        name: "Racing Engine",
+        slot: SlotKind::Engine,
+        cost: 300,
+        effect: ModuleEffect::Engine {
+            thrust: 1.5,
+            max_velocity: 1.3,
+        },
+    },
+    ModuleStats {
+        name: "Scanner Mk1",
+        slot: SlotKind::Scanner,
+        cost: 50,
+        effect: ModuleEffect::Scanner(ScannerStats {
+            scan_time: 35.0,
+            range: 3500.0,
+            ping_radius: 800.0,
+            ping_cooldown: 60.0,
+        }),
+    },
+    ModuleStats {
+        name: "Scanner Mk2",
+        slot: SlotKind::Scanner,
+        cost: 150,
+        effect: ModuleEffect::Scanner(ScannerStats {
+            scan_time: 25.0,
+            range: 4500.0,
+            ping_radius: 1500.0,
+            ping_cooldown: 45.0,
+        }),
+    },
+    ModuleStats {
+        name: "Scanner Mk3",
+        slot: SlotKind::Scanner,
+        cost: 400,
+        effect: ModuleEffect::Scanner(ScannerStats {
+            scan_time: 15.0,
+            range: 6000.0,
+            ping_radius: 2500.0,
+            ping_cooldown: 30.0,
+        }),
+    },
+    ModuleStats {
+        name: "Auxiliary Tank",
+        slot: SlotKind::FuelTank,
+        cost: 100,
+        effect: ModuleEffect::FuelTank(15),
+    },
+    ModuleStats {
+        name: "Expanded Hold",
+        slot: SlotKind::Cargo,
+        cost: 120,
+        effect: ModuleEffect::Cargo(50),
+    },
+    ModuleStats {
+        name: "Deflector Shield",
+        slot: SlotKind::Shield,
+        cost: 150,
+        effect: ModuleEffect::Shield {
+            capacity: 30.,
+            recharge: 6.,
+        },
+    },
+    ModuleStats {
+        name: "Heavy Shield",
+        slot: SlotKind::Shield,
+        cost: 350,
+        effect: ModuleEffect::Shield {
+            capacity: 60.,
+            recharge: 8.,
+        },
+    },
+];
+
+/// Looks up a module by name, returning its index into [`MODULES`]
+pub fn module_index(name: &str) -> Option<usize> {
+    MODULES.iter().position(|module| module.name == name)
+}
+
+/// A slot on a ship and the index into [`MODULES`] of what's installed in it
+#[derive(Clone, Copy)]
+pub struct Slot {
+    pub kind: SlotKind,
+    pub module: Option<usize>,
+}
+
+/// The ship's slots and their modules. Changing this recomputes the ship's
+/// stats from its [`BaseStats`].
+#[derive(Component, Clone)]
+pub struct Loadout(pub Vec<Slot>);
+
+impl Loadout {
+    pub fn installed(&self) -> impl Iterator<Item = &'static ModuleStats> + '_ {
+        self.0
+            .iter()
+            .filter_map(|slot| slot.module)
+            .map(|module| &MODULES[module])
+    }
+
+    /// The slot holding a module of this kind, if any
+    pub fn slot_with(&self, kind: SlotKind) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|slot| slot.kind == kind && slot.module.is_some())
+    }
+
+    /// The first empty slot of this kind
+    pub fn free_slot_of(&self, kind: SlotKind) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|slot| slot.kind == kind && slot.module.is_none())
+    }
+
+    /// Where buying `module` would put it, and what it would cost after
+    /// trading in the module it replaces. `None` if the ship has no slot for
+    /// it, or already has it in that slot.
+    pub fn quote(&self, module: usize) -> Option<(usize, u32)> {
+        let stats = &MODULES[module];
+        let slot = self
+            .free_slot_of(stats.slot)
+            .or_else(|| self.0.iter().position(|slot| slot.kind == stats.slot))?;
+
+        let replaced = self.0[slot].module;
+        if replaced == Some(module) {
+            return None;
+        }
+
+        Some((
+            slot,
+            stats.cost.saturating_sub(replaced.map_or(0, trade_in)),
+        ))
+    }
+
+    /// Puts `module` in `slot`, returning the module it replaces
+    pub fn install(&mut self, slot: usize, module: usize) -> Option<usize> {
+        debug_assert_eq!(self.0[slot].kind, MODULES[module].slot);
+
+        self.0[slot].module.replace(module)
+    }
+
+    /// Empties `slot`, returning the module that was in it
+    pub fn remove(&mut self, slot: usize) -> Option<usize> {
+        self.0[slot].module.take()
+    }
+}
+
+/// Credits given for a module when it's sold or replaced
+pub fn trade_in(module: usize) -> u32 {
+    MODULES[module].cost / 2
+}
+
+/// The hull's stats before any modules are applied
+#[derive(Component)]
+pub struct BaseStats {
+    pub thrust: f32,
+    pub max_velocity: f32,
+    pub fuel_capacity: u32,
+    pub cargo_capacity: u32,
+}
+
+/// Spacing between weapons mounted side by side
+const WEAPON_SPACING: f32 = 8.;
+
+fn apply(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &Loadout,
+            &BaseStats,
+            &mut Thrust,
+            &mut MaxVelocity,
+            &mut FuelTank,
+            &mut CargoHold,
+            &mut ScannerModule,
+            &mut Shield,
+            Option<&Children>,
+        ),
+        Changed<Loadout>,
+    >,
+    mut weapon_query: Query<(&mut BasicLaser, &mut Transform)>,
+) {
+    for (
+        entity,
+        loadout,
+        base,
+        mut thrust,
+        mut max_velocity,
+        mut fuel_tank,
+        mut cargo_hold,
+        mut scanner_module,
+        mut shield,
+        children,
+    ) in query.iter_mut()
+    {
+        thrust.0 = base.thrust;
+        max_velocity.0 = base.max_velocity;
+        fuel_tank.max = base.fuel_capacity;
+        cargo_hold.0 = base.cargo_capacity;
+
+        // A full shield stays full when it changes, so that new ships and
+        // newly installed shields start charged
+        let shield_full = shield.current >= shield.max;
+        shield.max = 0.;
+        shield.recharge_rate = 0.;
+
+        // Without a scanner installed, the ship falls back to its built in
+        // sensors
+        let mut scanner = ScannerModule::default();
+        let mut lasers = vec![];
+
+        for module in loadout.installed() {
+            match module.effect {
+                ModuleEffect::Laser { cooldown, damage } => lasers.push((cooldown, damage)),
+                ModuleEffect::Engine {
+                    thrust: thrust_multiplier,
+                    max_velocity: max_velocity_multiplier,
+                } => {
+                    thrust.0 *= thrust_multiplier;
+                    max_velocity.0 *= max_velocity_multiplier;
+                }
+                ModuleEffect::Scanner(stats) => {
+                    scanner = ScannerModule {
+                        name: module.name,
+                        stats,
+                    }
+                }
+                ModuleEffect::FuelTank(capacity) => fuel_tank.max += capacity,
+                ModuleEffect::Cargo(capacity) => cargo_hold.0 += capacity,
+                ModuleEffect::Shield { capacity, recharge } => {
+                    shield.max += capacity;
+                    shield.recharge_rate += recharge;
+                }
+            }
+        }
+
+        fuel_tank.current = fuel_tank.current.min(fuel_tank.max);
+        shield.current = if shield_full {
+            shield.max
+        } else {
+            shield.current.min(shield.max)
+        };
+
+        if *scanner_module != scanner {
+            *scanner_module = scanner;
+        }
+
+        // Weapons already mounted are kept and refitted, so that swapping one
+        // module doesn't reset the others' cooldowns
+        let guns: Vec<Entity> = children
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|child| weapon_query.contains(*child))
+            .collect();
+
+        let offset = (lasers.len() as f32 - 1.) / 2.;
+
+        for (i, (cooldown, damage)) in lasers.iter().copied().enumerate() {
+            let translation = Vec3::new(0., (i as f32 - offset) * WEAPON_SPACING, 0.);
+            let cooldown = Duration::from_secs_f32(cooldown);
+
+            let Some(gun) = guns.get(i) else {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        SpatialBundle::from_transform(Transform::from_translation(translation)),
+                        BasicLaser {
+                            timer: Timer::new(cooldown, TimerMode::Repeating),
+                            damage,
+                        },
+                    ));
+                });
+                continue;
+            };
+
+            let (mut laser, mut transform) = weapon_query.get_mut(*gun).unwrap();
+            transform.translation = translation;
+            laser.damage = damage;
+
+            let elapsed = laser.timer.elapsed().min(cooldown);
+            laser.timer.set_duration(cooldown);
+            laser.timer.set_elapsed(elapsed);
+        }
+
+        for gun in guns.iter().skip(lasers.len()) {
+            commands.entity(*gun).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship(world: &mut World) -> Entity {
+        world
+            .spawn((
+                Loadout(vec![
+                    Slot {
+                        kind: SlotKind::Weapon,
+                        module: module_index("Basic Laser"),
+                    },
+                    Slot {
+                        kind: SlotKind::Engine,
+                        module: None,
+                    },
+                    Slot {
+                        kind: SlotKind::Scanner,
+                        module: None,
+                    },
+                ]),
+                BaseStats {
+                    thrust: 100.,
+                    max_velocity: 100.,
+                    fuel_capacity: 30,
+                    cargo_capacity: 100,
+                },
+                Thrust(0.),
+                MaxVelocity(0.),
+                FuelTank { current: 0, max: 0 },
+                CargoHold(0),
+                ScannerModule::default(),
+                Shield::default(),
+            ))
+            .id()
+    }
+
+    fn guns(world: &mut World) -> Vec<(Entity, f32, Duration)> {
+        world
+            .query::<(Entity, &BasicLaser)>()
+            .iter(world)
+            .map(|(entity, laser)| (entity, laser.damage, laser.timer.elapsed()))
+            .collect()
+    }
+
+    #[test]
+    fn installing_and_removing_recomputes_stats() {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply);
+
+        let ship = ship(&mut world);
+        schedule.run(&mut world);
+        assert_eq!(world.get::<Thrust>(ship).unwrap().0, 100.);
+
+        let engine = module_index("Racing Engine").unwrap();
+        let scanner = module_index("Scanner Mk2").unwrap();
+        {
+            let mut loadout = world.get_mut::<Loadout>(ship).unwrap();
+            let (slot, _) = loadout.quote(engine).unwrap();
+            loadout.install(slot, engine);
+            let (slot, _) = loadout.quote(scanner).unwrap();
+            loadout.install(slot, scanner);
+        }
+        schedule.run(&mut world);
+        assert_eq!(world.get::<Thrust>(ship).unwrap().0, 150.);
+        assert_eq!(world.get::<MaxVelocity>(ship).unwrap().0, 130.);
+        assert_eq!(
+            world.get::<ScannerModule>(ship).unwrap().name,
+            "Scanner Mk2"
+        );
+
+        world.get_mut::<Loadout>(ship).unwrap().remove(1);
+        schedule.run(&mut world);
+        assert_eq!(world.get::<Thrust>(ship).unwrap().0, 100.);
+        assert_eq!(world.get::<MaxVelocity>(ship).unwrap().0, 100.);
+    }
+
+    #[test]
+    fn swapping_weapons_keeps_the_mount() {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply);
+
+        let ship = ship(&mut world);
+        schedule.run(&mut world);
+
+        let (gun, _, _) = guns(&mut world)[0];
+        world
+            .get_mut::<BasicLaser>(gun)
+            .unwrap()
+            .timer
+            .tick(Duration::from_secs_f32(0.4));
+
+        let heavy = module_index("Heavy Laser").unwrap();
+        let mut loadout = world.get_mut::<Loadout>(ship).unwrap();
+        let (slot, _) = loadout.quote(heavy).unwrap();
+        loadout.install(slot, heavy);
+        schedule.run(&mut world);
+
+        assert_eq!(
+            guns(&mut world),
+            vec![(gun, 3., Duration::from_secs_f32(0.4))]
+        );
+    }
+}
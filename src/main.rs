@@ -1,29 +1,72 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
-use basic_laser::{BasicLaser, BasicLaserPlugin};
-use bevy::prelude::*;
-use bevy_asset_loader::prelude::*;
-use bevy_spatial::{AutomaticUpdate, SpatialStructure};
-use commodity::{CommodityInventory, CommodityPlugin};
-use direction_indicator::{
-    DirectionIndicator, DirectionIndicatorPlugin, DirectionIndicatorSettings,
+use anomaly::AnomalyPlugin;
+use basic_laser::BasicLaserPlugin;
+use bevy::{
+    input::gamepad::{ButtonSettings, GamepadSettings},
+    prelude::*,
+    window::PrimaryWindow,
 };
+use bevy_asset_loader::prelude::*;
+use camera::{CameraController, CameraControllerPlugin};
+use celestial::CelestialPlugin;
+use combat_feedback::CombatFeedbackPlugin;
+use commodity::{CargoHold, CommodityInventory, CommodityPlugin};
+use controls::{Controls, ControlsPlugin};
+use direction_indicator::DirectionIndicatorPlugin;
 use enemy::EnemyPlugin;
+use enemy_status::EnemyStatusPlugin;
+use flight::{Autopilot, Boost, FlightPlugin, InertialDampers, Strafe};
 use fuel::FuelPlugin;
+use gravity::{AffectedByGravity, GravityPlugin};
+use hull::{HullPlugin, Hulls};
 use leafwing_input_manager::prelude::*;
-use scanner::ScannerPlugin;
+use loadout::{BaseStats, LoadoutPlugin, MODULES};
+use menu::MainMenuPlugin;
+use particle::{ParticleEmitter, ParticlePlugin, ParticleSettings};
+use pause::PausePlugin;
+use physics::{Interpolated, PhysicsPlugin};
+use profile::ProfilePlugin;
+use radar::RadarPlugin;
+use run::{RunPlugin, RunSetup};
+use scanner::{ScannerModule, ScannerPlugin};
+use serde::{Deserialize, Serialize};
+use shield::{Shield, ShieldBubble, ShieldPlugin};
+use shop::ShopPlugin;
 use starfield::StarfieldPlugin;
+use system_event::SystemEventPlugin;
 use ui::UiPlugin;
 use warp_node::{WarpNodePlugin, WarpedTo};
 
+mod anomaly;
 mod basic_laser;
+mod camera;
+mod celestial;
+mod combat_feedback;
 mod commodity;
+mod controls;
 mod direction_indicator;
 mod enemy;
+mod enemy_status;
+mod flight;
 pub mod fuel;
+mod gravity;
+mod hull;
 mod layer;
+mod loadout;
+mod menu;
+mod particle;
+mod pause;
+mod physics;
+mod pool;
+mod profile;
+mod radar;
+mod run;
 mod scanner;
+mod shield;
+mod shop;
 mod starfield;
+mod system_event;
 mod ui;
 mod util;
 mod warp_node;
@@ -35,46 +78,75 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_state::<GameState>()
         .add_loading_state(
-            LoadingState::new(GameState::Loading).continue_to_state(GameState::Playing),
+            LoadingState::new(GameState::Loading).continue_to_state(GameState::MainMenu),
         )
         .add_collection_to_loading_state::<_, Fonts>(GameState::Loading)
         .add_plugins(InputManagerPlugin::<Action>::default())
-        .add_plugins(
-            AutomaticUpdate::<SpatialIndex>::new().with_spatial_ds(SpatialStructure::KDTree2),
-        )
+        .add_plugins(ControlsPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(RunPlugin)
+        .add_plugins(ProfilePlugin)
+        .add_plugins(HullPlugin)
+        .add_plugins(MainMenuPlugin)
+        // Triggers count as pressed as soon as they move, so that they give
+        // proportional thrust over their whole range
+        .insert_resource(GamepadSettings {
+            default_button_settings: ButtonSettings::new(0.05, 0.04).unwrap(),
+            ..default()
+        })
+        .add_plugins(PhysicsPlugin)
+        .add_plugins(FlightPlugin)
+        .add_plugins(CameraControllerPlugin)
         .add_plugins(StarfieldPlugin)
+        .add_plugins(CelestialPlugin)
+        .add_plugins(GravityPlugin)
         .add_plugins(BasicLaserPlugin)
+        .add_plugins(LoadoutPlugin)
+        .add_plugins(ShieldPlugin)
+        .add_plugins(ParticlePlugin)
+        .add_plugins(CombatFeedbackPlugin)
         .add_plugins(EnemyPlugin)
+        .add_plugins(EnemyStatusPlugin)
         .add_plugins(FuelPlugin)
         .add_plugins(DirectionIndicatorPlugin)
+        .add_plugins(RadarPlugin)
         .add_plugins(CommodityPlugin)
         .add_plugins(ScannerPlugin)
+        .add_plugins(ShopPlugin)
         .add_plugins(WarpNodePlugin)
+        .add_plugins(AnomalyPlugin)
+        .add_plugins(SystemEventPlugin)
         .add_plugins(UiPlugin);
 
-    app.add_systems(OnExit(GameState::Loading), spawn_player)
-        .add_systems(OnEnter(GameState::Playing), spawn_level)
-        .add_systems(
-            Update,
-            (
-                player_input,
-                thruster.before(acceleration),
-                acceleration.before(apply_acceleration),
-                apply_acceleration.before(movement),
-                rotation.before(movement),
-                movement,
-                move_camera.after(movement),
-            )
-                .run_if(in_state(GameState::Playing))
-                .in_set(MovementSet),
-        )
-        .add_systems(
-            Update,
-            (warp_movement, move_camera.after(warp_movement))
-                .run_if(in_state(GameState::Warping))
-                .in_set(MovementSet),
+    app.add_systems(
+        Update,
+        (player_input, thruster.after(player_input)).run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            acceleration.before(apply_acceleration),
+            apply_acceleration.before(movement),
+            rotation.before(movement),
+            movement,
         )
-        .add_systems(OnExit(GameState::Warping), (cleanup, sell, reset_player));
+            .run_if(in_state(GameState::Playing))
+            .in_set(PhysicsSet),
+    )
+    .add_systems(
+        FixedUpdate,
+        warp_movement
+            .run_if(in_state(GameState::Warping))
+            .in_set(PhysicsSet),
+    )
+    .add_systems(OnExit(GameState::Warping), (cleanup, sell, reset_player))
+    .add_systems(
+        OnEnter(GameState::Restarting),
+        (run::start, despawn_player, spawn_player, restart).chain(),
+    )
+    .add_systems(OnExit(GameState::Restarting), cleanup)
+    // Leaving a run for the main menu
+    .add_systems(OnEnter(GameState::MainMenu), (cleanup, despawn_player));
 
     app.run();
 }
@@ -82,12 +154,19 @@ fn main() {
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub struct MovementSet;
 
+/// Systems that run on the fixed timestep and move things around
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct PhysicsSet;
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 enum GameState {
     #[default]
     Loading,
+    MainMenu,
     Playing,
     Warping,
+    /// Tearing down any current run and starting a new one
+    Restarting,
 }
 
 #[derive(Resource, AssetCollection)]
@@ -97,17 +176,57 @@ struct Fonts {
 }
 
 // This is the list of "things in the game I want to be able to do based on input"
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 enum Action {
     TurnLeft,
     TurnRight,
     Forward,
     Reverse,
+    StrafeLeft,
+    StrafeRight,
+    Boost,
+    ToggleDampers,
+    /// Analog turning, e.g. from a stick's horizontal axis
+    Turn,
+    /// Point the ship in a direction, e.g. with the right stick
+    Aim,
+    ToggleMouseSteering,
+    CycleScanMode,
+    ScannerPing,
+    UpgradeScanner,
+    /// Buy hull repairs while near a planet
+    Repair,
+    ToggleGravity,
+    CycleRadarRange,
+    CycleRadarFilter,
+    /// Pin the indicator the ship is pointing at as the waypoint
+    PinWaypoint,
+    /// Fly to the pinned waypoint
+    ToggleAutopilot,
+    /// Show or hide health bars and status icons over enemies
+    ToggleEnemyStatus,
+    /// Buy and sell modules while near a planet
+    OpenShop,
 }
 
 #[derive(Component)]
 pub struct Player;
 #[derive(Component)]
+pub struct Planet;
+#[derive(Component)]
 struct PlayerThruster;
 #[derive(Component, Default)]
 struct Acceleration(Vec2);
@@ -127,6 +246,16 @@ enum ThrusterStatus {
     Reverse,
     None,
 }
+/// How hard the thrusters are firing, from 0 to 1
+#[derive(Component)]
+struct Throttle(f32);
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum SteeringMode {
+    /// Turn with the turn actions and sticks
+    Direct,
+    /// Turn to face the mouse cursor
+    Mouse,
+}
 #[derive(Component)]
 struct MaxVelocity(f32);
 #[derive(Component)]
@@ -152,113 +281,122 @@ fn spawn_player(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    controls: Res<Controls>,
+    setup: Res<RunSetup>,
+    hulls: Res<Hulls>,
 ) {
-    commands.spawn(Camera2dBundle::default());
-
-    let mut input_map = InputMap::new([
-        (KeyCode::A, Action::TurnLeft),
-        (KeyCode::Left, Action::TurnLeft),
-        (KeyCode::D, Action::TurnRight),
-        (KeyCode::Right, Action::TurnRight),
-        (KeyCode::W, Action::Forward),
-        (KeyCode::Up, Action::Forward),
-        (KeyCode::S, Action::Reverse),
-        (KeyCode::Down, Action::Reverse),
-    ]);
-
-    input_map.insert_multiple([
-        (GamepadButtonType::DPadLeft, Action::TurnLeft),
-        (GamepadButtonType::DPadRight, Action::TurnRight),
-        (GamepadButtonType::DPadUp, Action::Forward),
-        (GamepadButtonType::DPadDown, Action::Reverse),
-    ]);
+    let hull = &hulls.0[setup.hull];
+    let saved = setup.saved.as_ref();
+
+    let transform = Transform::from_xyz(0., 0., layer::SHIP);
+
+    // A continued run keeps the modules it had, as long as they still fit the hull
+    let mut loadout = hull.loadout();
+    if let Some(modules) = saved
+        .map(|saved| &saved.modules)
+        .filter(|modules| modules.len() == loadout.0.len())
+    {
+        for (slot, module) in loadout.0.iter_mut().zip(modules) {
+            slot.module = module.filter(|module| {
+                MODULES
+                    .get(*module)
+                    .is_some_and(|stats| stats.slot == slot.kind)
+            });
+        }
+    }
 
     commands
         .spawn((
             SpatialBundle {
-                transform: Transform::from_xyz(0., 0., layer::SHIP),
+                transform,
                 ..default()
             },
             Player,
-            Acceleration::default(),
-            Velocity::default(),
-            AngularVelocity::default(),
-            Rotation(std::f32::consts::FRAC_PI_2),
-            RotationSpeed(2.),
-            Thrust(100.),
-            ThrusterStatus::None,
-            MaxVelocity(100.),
+            (
+                Interpolated::new(&transform),
+                Acceleration::default(),
+                Velocity::default(),
+                AngularVelocity::default(),
+                Rotation(std::f32::consts::FRAC_PI_2),
+                AffectedByGravity,
+            ),
+            RotationSpeed(hull.rotation_speed),
+            Thrust(hull.thrust),
+            (
+                ThrusterStatus::None,
+                Throttle(0.),
+                SteeringMode::Direct,
+                Strafe::default(),
+                InertialDampers {
+                    enabled: false,
+                    strength: 1.5,
+                },
+                Boost::new(100.),
+                Autopilot::default(),
+            ),
+            MaxVelocity(hull.max_velocity),
             FuelTank {
-                current: 0,
-                max: 30,
-            },
-            Credits(0),
-            BasicLaser {
-                timer: Timer::from_seconds(1., TimerMode::Repeating),
-                damage: 1.,
+                current: saved.map_or(0, |saved| saved.fuel),
+                max: hull.fuel_capacity,
             },
+            Credits(saved.map_or(0, |saved| saved.credits)),
+            (
+                Health {
+                    current: saved.map_or(hull.health, |saved| saved.health.min(hull.health)),
+                    max: hull.health,
+                },
+                Shield::default(),
+            ),
             CommodityInventory::default(),
+            CargoHold(hull.cargo_capacity),
+            ScannerModule::default(),
+            (
+                BaseStats {
+                    thrust: hull.thrust,
+                    max_velocity: hull.max_velocity,
+                    fuel_capacity: hull.fuel_capacity,
+                    cargo_capacity: hull.cargo_capacity,
+                },
+                loadout,
+            ),
             InputManagerBundle::<Action> {
                 // Stores "which actions are currently pressed"
                 action_state: ActionState::default(),
                 // Describes how to convert from player inputs into those actions
-                input_map,
+                input_map: controls.0.clone(),
             },
         ))
         .with_children(|parent| {
             // ship body
             parent.spawn(ColorMesh2dBundle {
-                mesh: meshes.add(shape::RegularPolygon::new(20., 3).into()).into(),
-                material: materials.add(Color::RED.into()),
-                transform: Transform::from_rotation(Quat::from_rotation_z(
-                    -std::f32::consts::FRAC_PI_2,
-                )),
+                mesh: meshes.add(hull.shape.mesh()).into(),
+                material: materials.add(hull.color().into()),
+                transform: Transform::from_rotation(hull.shape.rotation()),
                 ..default()
             });
-            // thruster
-            parent
-                .spawn(ColorMesh2dBundle {
-                    mesh: meshes.add(shape::RegularPolygon::new(10., 3).into()).into(),
-                    material: materials.add(Color::ORANGE.into()),
-                    transform: Transform::from_rotation(Quat::from_rotation_z(
-                        std::f32::consts::FRAC_PI_2,
-                    ))
-                    .with_translation(Vec3::new(-10., 0., -0.1)),
+            // shield bubble, faded in and out as the shield charges
+            parent.spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes
+                        .add(shape::Circle::new(hull.shape.radius() + 6.).into())
+                        .into(),
+                    material: materials.add(Color::rgba(0.4, 0.8, 1., 0.).into()),
+                    transform: Transform::from_xyz(0., 0., 0.1),
+                    visibility: Visibility::Hidden,
                     ..default()
-                })
-                .insert(PlayerThruster);
+                },
+                ShieldBubble,
+            ));
+            // thruster exhaust
+            parent.spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(-hull.shape.rear(), 0., -0.1)),
+                // Switched on by the `thruster` system
+                ParticleEmitter::new(ParticleSettings::exhaust(), THRUSTER_PARTICLE_RATE),
+                PlayerThruster,
+            ));
         });
 }
 
-fn spawn_level(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    let planet = commands
-        .spawn((
-            ColorMesh2dBundle {
-                mesh: meshes.add(shape::Circle::new(60.).into()).into(),
-                material: materials.add(Color::AQUAMARINE.into()),
-                transform: Transform::from_xyz(0., 0., layer::PLANET),
-                ..default()
-            },
-            DespawnOnRestart,
-        ))
-        .id();
-
-    commands.spawn((
-        DirectionIndicator {
-            target: planet,
-            settings: DirectionIndicatorSettings {
-                color: Color::AQUAMARINE,
-                label: None,
-            },
-        },
-        DespawnOnRestart,
-    ));
-}
-
 fn sell(
     mut query: Query<(&mut Credits, &mut CommodityInventory), With<Player>>,
     warped_to: Option<Res<WarpedTo>>,
@@ -271,7 +409,7 @@ fn sell(
     let (mut credits, mut inventory) = query.single_mut();
 
     for (commodity, quantity) in inventory.0.drain() {
-        let multiplier = warped_to.0 .0.get(&commodity).unwrap_or(&1.);
+        let multiplier = warped_to.prices.0.get(&commodity).unwrap_or(&1.);
         let price = (quantity as f32 * multiplier).round() as u32;
         credits.0 += price;
     }
@@ -281,33 +419,75 @@ fn reset_player(
     mut query: Query<
         (
             &mut Transform,
-            &mut FuelTank,
+            &mut Interpolated,
             &mut Velocity,
             &mut AngularVelocity,
         ),
         With<Player>,
     >,
 ) {
-    for (mut transform, mut fuel, mut velocity, mut angular) in query.iter_mut() {
-        transform.translation = Vec2::ZERO.extend(layer::SHIP);
-        fuel.current = 0;
+    for (mut transform, mut interpolated, mut velocity, mut angular) in query.iter_mut() {
+        interpolated.teleport(&mut transform, Vec2::ZERO.extend(layer::SHIP));
         velocity.0 = Vec2::ZERO;
         angular.0 = 0.;
     }
 }
 
+fn despawn_player(mut commands: Commands, query: Query<Entity, With<Player>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn restart(mut setup: ResMut<RunSetup>, mut next_state: ResMut<NextState<GameState>>) {
+    // Only pick up a saved run once; restarting it starts from the beginning
+    setup.saved = None;
+
+    next_state.set(GameState::Playing);
+}
+
 fn cleanup(mut commands: Commands, query: Query<Entity, With<DespawnOnRestart>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+/// Scales how quickly the ship turns towards an aim direction, so that it
+/// eases in rather than overshooting
+const AIM_TURN_GAIN: f32 = 3.;
+
 fn player_input(
-    query: Query<&ActionState<Action>, With<Player>>,
-    mut player_query: Query<(&mut AngularVelocity, &mut ThrusterStatus), With<Player>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform, &CameraController)>,
+    mut query: Query<
+        (
+            &ActionState<Action>,
+            &Transform,
+            &Rotation,
+            &mut SteeringMode,
+            &mut AngularVelocity,
+            &mut ThrusterStatus,
+            &mut Throttle,
+        ),
+        With<Player>,
+    >,
 ) {
-    let action_state = query.single();
-    let (mut angular, mut thruster_status) = player_query.single_mut();
+    let (
+        action_state,
+        transform,
+        rotation,
+        mut steering_mode,
+        mut angular,
+        mut thruster_status,
+        mut throttle,
+    ) = query.single_mut();
+
+    if action_state.just_pressed(Action::ToggleMouseSteering) {
+        *steering_mode = match *steering_mode {
+            SteeringMode::Direct => SteeringMode::Mouse,
+            SteeringMode::Mouse => SteeringMode::Direct,
+        };
+    }
 
     let mut new_angular = 0.;
 
@@ -319,13 +499,50 @@ fn player_input(
         new_angular -= 1.0;
     }
 
-    let new_thruster =
+    // Pushing the stick right turns clockwise
+    new_angular -= action_state.clamped_value(Action::Turn);
+
+    let cursor_direction = || {
+        let cursor = windows.get_single().ok()?.cursor_position()?;
+        let (camera, camera_transform, controller) = camera_query.get_single().ok()?;
+
+        // Aim through the camera as it would be without screen shake, so
+        // that the aim point doesn't jitter with it
+        let unshaken = GlobalTransform::from_translation(
+            controller.focus.extend(camera_transform.translation().z),
+        );
+        let target = camera.viewport_to_world_2d(&unshaken, cursor)?;
+
+        Some(target - transform.translation.truncate())
+    };
+
+    let aim = action_state
+        .clamped_axis_pair(Action::Aim)
+        .map(|axis| axis.xy())
+        .filter(|xy| *xy != Vec2::ZERO)
+        .or_else(|| match *steering_mode {
+            SteeringMode::Mouse => cursor_direction(),
+            SteeringMode::Direct => None,
+        });
+
+    if let Some(direction) = aim {
+        let heading = Vec2::from_angle(rotation.0);
+
+        new_angular = heading.angle_between(direction) * AIM_TURN_GAIN;
+    }
+
+    let new_angular = new_angular.clamp(-1., 1.);
+
+    let forward = action_state.clamped_value(Action::Forward);
+    let reverse = action_state.clamped_value(Action::Reverse);
+
+    let (new_thruster, new_throttle) =
         if action_state.pressed(Action::Forward) && !action_state.pressed(Action::Reverse) {
-            ThrusterStatus::Forward
+            (ThrusterStatus::Forward, forward)
         } else if action_state.pressed(Action::Reverse) && !action_state.pressed(Action::Forward) {
-            ThrusterStatus::Reverse
+            (ThrusterStatus::Reverse, reverse)
         } else {
-            ThrusterStatus::None
+            (ThrusterStatus::None, 0.)
         };
 
     if angular.0 != new_angular {
@@ -335,10 +552,22 @@ fn player_input(
     if *thruster_status != new_thruster {
         *thruster_status = new_thruster;
     }
+
+    if throttle.0 != new_throttle {
+        throttle.0 = new_throttle;
+    }
 }
 
-fn acceleration(mut query: Query<(&mut Acceleration, &Thrust, &ThrusterStatus, &Rotation)>) {
-    for (mut acceleration, thrust, thruster_status, rotation) in query.iter_mut() {
+fn acceleration(
+    mut query: Query<(
+        &mut Acceleration,
+        &Thrust,
+        &Throttle,
+        &ThrusterStatus,
+        &Rotation,
+    )>,
+) {
+    for (mut acceleration, thrust, throttle, thruster_status, rotation) in query.iter_mut() {
         match thruster_status {
             ThrusterStatus::Forward => {
                 let sin_cos = rotation.0.sin_cos();
@@ -355,22 +584,28 @@ fn acceleration(mut query: Query<(&mut Acceleration, &Thrust, &ThrusterStatus, &
             }
         }
 
-        acceleration.0 *= thrust.0;
+        acceleration.0 *= thrust.0 * throttle.0;
     }
 }
 
 fn apply_acceleration(
-    time: Res<Time>,
-    mut query: Query<(&mut Velocity, &Acceleration, &MaxVelocity)>,
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(&mut Velocity, &Acceleration, &MaxVelocity, Option<&Boost>)>,
 ) {
-    for (mut velocity, acceleration, max_velocity) in query.iter_mut() {
-        velocity.0 += acceleration.0 * time.delta_seconds();
-        velocity.0 = velocity.0.clamp_length_max(max_velocity.0);
+    for (mut velocity, acceleration, max_velocity, boost) in query.iter_mut() {
+        let max_velocity = max_velocity.0 * boost.map_or(1., Boost::max_velocity_multiplier);
+
+        // Thrust can't push past the max velocity, but speed picked up from
+        // elsewhere (e.g. a gravity assist) is kept.
+        let limit = velocity.0.length().max(max_velocity);
+
+        velocity.0 += acceleration.0 * fixed_time.period.as_secs_f32();
+        velocity.0 = velocity.0.clamp_length_max(limit);
     }
 }
 
 fn rotation(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut query: Query<(
         &mut Transform,
         &AngularVelocity,
@@ -379,46 +614,41 @@ fn rotation(
     )>,
 ) {
     for (mut transform, angular, mut rotation, rotation_speed) in query.iter_mut() {
-        rotation.0 += angular.0 * time.delta_seconds() * rotation_speed.0;
+        rotation.0 += angular.0 * fixed_time.period.as_secs_f32() * rotation_speed.0;
 
         transform.rotation = Quat::from_rotation_z(rotation.0);
     }
 }
 
-fn movement(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
+fn movement(fixed_time: Res<FixedTime>, mut query: Query<(&mut Transform, &Velocity)>) {
     for (mut transform, velocity) in query.iter_mut() {
-        transform.translation += (velocity.0 * time.delta_seconds()).extend(0.);
+        transform.translation += (velocity.0 * fixed_time.period.as_secs_f32()).extend(0.);
     }
 }
 
-fn warp_movement(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
+fn warp_movement(fixed_time: Res<FixedTime>, mut query: Query<(&mut Transform, &Velocity)>) {
     for (mut transform, velocity) in query.iter_mut() {
-        transform.translation += (velocity.0 * time.delta_seconds() / 7.).extend(0.);
+        transform.translation += (velocity.0 * fixed_time.period.as_secs_f32() / 7.).extend(0.);
     }
 }
 
+/// Exhaust particles per second at full throttle
+const THRUSTER_PARTICLE_RATE: f32 = 60.;
+
 fn thruster(
-    mut thruster_query: Query<&mut Visibility, With<PlayerThruster>>,
-    status_query: Query<&ThrusterStatus, (Changed<ThrusterStatus>, With<Player>)>,
+    mut thruster_query: Query<&mut ParticleEmitter, With<PlayerThruster>>,
+    status_query: Query<
+        (&ThrusterStatus, &Throttle),
+        (
+            Or<(Changed<ThrusterStatus>, Changed<Throttle>)>,
+            With<Player>,
+        ),
+    >,
 ) {
-    for status in status_query.iter() {
-        let mut thruster = thruster_query.single_mut();
+    for (status, throttle) in status_query.iter() {
+        let mut emitter = thruster_query.single_mut();
 
-        *thruster = if matches!(status, ThrusterStatus::Forward) {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        }
+        emitter.active = matches!(status, ThrusterStatus::Forward);
+        emitter.rate = THRUSTER_PARTICLE_RATE * throttle.0;
     }
 }
-
-fn move_camera(
-    player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
-) {
-    let player = player_query.single();
-    let mut camera = camera_query.single_mut();
-
-    camera.translation.x = player.translation.x;
-    camera.translation.y = player.translation.y;
-}
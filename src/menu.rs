@@ -0,0 +1,335 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    controls::{ControlsMenu, OpenControlsEvent},
+    hull::Hulls,
+    profile::Profile,
+    run::{RunSetup, SavedRun},
+    Fonts, GameState,
+};
+
+pub struct MainMenuPlugin;
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuScreen>();
+
+        app.add_systems(OnEnter(GameState::MainMenu), enter);
+        app.add_systems(OnExit(GameState::MainMenu), despawn);
+        app.add_systems(
+            Update,
+            (click, seed_input, rebuild.after(click).after(seed_input))
+                .run_if(in_state(GameState::MainMenu)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+enum MenuScreen {
+    #[default]
+    Main,
+    /// Choosing a ship and seed for a new run
+    RunSetup,
+    Credits,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    NewRun,
+    Continue,
+    Settings,
+    Credits,
+    Quit,
+    PreviousHull,
+    NextHull,
+    ClearSeed,
+    Start,
+    Back,
+}
+
+impl MenuButton {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::NewRun => "New Run",
+            Self::Continue => "Continue",
+            Self::Settings => "Settings",
+            Self::Credits => "Credits",
+            Self::Quit => "Quit",
+            Self::PreviousHull => "<",
+            Self::NextHull => ">",
+            Self::ClearSeed => "Random Seed",
+            Self::Start => "Launch",
+            Self::Back => "Back",
+        }
+    }
+}
+
+fn enter(mut screen: ResMut<MenuScreen>) {
+    // Always mark as changed so the menu gets built
+    *screen = MenuScreen::Main;
+}
+
+fn despawn(mut commands: Commands, query: Query<Entity, With<MenuRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn click(
+    query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    controls_menu: Res<ControlsMenu>,
+    mut screen: ResMut<MenuScreen>,
+    mut setup: ResMut<RunSetup>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut open_controls: EventWriter<OpenControlsEvent>,
+    mut exit: EventWriter<AppExit>,
+    hulls: Res<Hulls>,
+    profile: Res<Profile>,
+) {
+    let num_hulls = hulls.0.len();
+
+    // The controls screen sits on top of this menu
+    if controls_menu.is_open() {
+        return;
+    }
+
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::NewRun => {
+                setup.saved = None;
+                setup.seed = None;
+                *screen = MenuScreen::RunSetup;
+            }
+            MenuButton::Continue => {
+                if let Some(saved) = SavedRun::load() {
+                    setup.hull = saved.hull.min(num_hulls - 1);
+                    setup.saved = Some(saved);
+                    next_state.set(GameState::Restarting);
+                }
+            }
+            MenuButton::Settings => open_controls.send(OpenControlsEvent),
+            MenuButton::Credits => *screen = MenuScreen::Credits,
+            MenuButton::Quit => exit.send(AppExit),
+            MenuButton::PreviousHull => setup.hull = (setup.hull + num_hulls - 1) % num_hulls,
+            MenuButton::NextHull => setup.hull = (setup.hull + 1) % num_hulls,
+            MenuButton::ClearSeed => setup.seed = None,
+            MenuButton::Start => {
+                if hulls.0[setup.hull].unlocked(&profile) {
+                    next_state.set(GameState::Restarting);
+                }
+            }
+            MenuButton::Back => *screen = MenuScreen::Main,
+        }
+    }
+}
+
+/// Lets the player type a seed on the run setup screen
+fn seed_input(
+    mut chars: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    screen: Res<MenuScreen>,
+    mut setup: ResMut<RunSetup>,
+) {
+    if *screen != MenuScreen::RunSetup {
+        chars.clear();
+        return;
+    }
+
+    for event in chars.iter() {
+        let Some(digit) = event.char.to_digit(10) else {
+            continue;
+        };
+
+        let seed = setup
+            .seed
+            .unwrap_or(0)
+            .checked_mul(10)
+            .and_then(|seed| seed.checked_add(digit as u64));
+
+        if seed.is_some() {
+            setup.seed = seed;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) && setup.seed.is_some() {
+        setup.seed = setup.seed.filter(|seed| *seed >= 10).map(|seed| seed / 10);
+    }
+}
+
+fn rebuild(
+    mut commands: Commands,
+    screen: Res<MenuScreen>,
+    setup: Res<RunSetup>,
+    query: Query<Entity, With<MenuRoot>>,
+    fonts: Res<Fonts>,
+    hulls: Res<Hulls>,
+    profile: Res<Profile>,
+) {
+    if !screen.is_changed() && !setup.is_changed() {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text_style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 20.,
+        color: Color::BEIGE,
+    };
+    let title_style = TextStyle {
+        font_size: 40.,
+        ..text_style.clone()
+    };
+
+    let button = |parent: &mut ChildBuilder, button: MenuButton| {
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        min_width: Val::Px(60.),
+                        margin: UiRect::all(Val::Px(4.)),
+                        padding: UiRect::all(Val::Px(8.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                },
+                button,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(button.label(), text_style.clone()));
+            });
+    };
+
+    let row = |parent: &mut ChildBuilder, build: &dyn Fn(&mut ChildBuilder)| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    margin: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| build(parent));
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| match *screen {
+            MenuScreen::Main => {
+                parent.spawn(TextBundle::from_section(
+                    "Untitled Space Game",
+                    title_style.clone(),
+                ));
+
+                button(parent, MenuButton::NewRun);
+                if SavedRun::load().is_some() {
+                    button(parent, MenuButton::Continue);
+                }
+                button(parent, MenuButton::Settings);
+                button(parent, MenuButton::Credits);
+                button(parent, MenuButton::Quit);
+            }
+            MenuScreen::RunSetup => {
+                let hull = &hulls.0[setup.hull];
+                let unlocked = hull.unlocked(&profile);
+
+                parent.spawn(TextBundle::from_section("New Run", title_style.clone()));
+
+                row(parent, &|parent| {
+                    button(parent, MenuButton::PreviousHull);
+                    parent.spawn(
+                        TextBundle::from_section(&hull.name, text_style.clone()).with_style(
+                            Style {
+                                margin: UiRect::horizontal(Val::Px(20.)),
+                                ..default()
+                            },
+                        ),
+                    );
+                    button(parent, MenuButton::NextHull);
+                });
+
+                parent.spawn(
+                    TextBundle::from_section(
+                        format!(
+                            "{}\nThrust {:.0}  Speed {:.0}  Turning {:.1}\n\
+                             Fuel {}  Cargo {}\nSlots: {}",
+                            hull.description,
+                            hull.thrust,
+                            hull.max_velocity,
+                            hull.rotation_speed,
+                            hull.fuel_capacity,
+                            hull.cargo_capacity,
+                            hull.describe_slots(),
+                        ),
+                        text_style.clone(),
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+
+                row(parent, &|parent| {
+                    let seed = setup
+                        .seed
+                        .map_or_else(|| "Random".to_string(), |seed| seed.to_string());
+
+                    parent.spawn(TextBundle::from_section(
+                        format!("Seed {} (type to change)", seed),
+                        text_style.clone(),
+                    ));
+                    button(parent, MenuButton::ClearSeed);
+                });
+
+                if let Some(unlock) = hull.unlock.as_ref().filter(|_| !unlocked) {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Locked - {}", unlock.describe()),
+                        TextStyle {
+                            color: Color::RED,
+                            ..text_style.clone()
+                        },
+                    ));
+                }
+
+                row(parent, &|parent| {
+                    button(parent, MenuButton::Back);
+                    if unlocked {
+                        button(parent, MenuButton::Start);
+                    }
+                });
+            }
+            MenuScreen::Credits => {
+                parent.spawn(TextBundle::from_section("Credits", title_style.clone()));
+                parent.spawn(
+                    TextBundle::from_section(
+                        "Made with Bevy\nOrbitron font by Matt McInerney",
+                        text_style.clone(),
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+
+                button(parent, MenuButton::Back);
+            }
+        });
+}
@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    layer,
+    pool::{release_all, Pool, Slot},
+    GameState,
+};
+
+pub struct ParticlePlugin;
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ParticleBurstEvent>();
+        app.insert_resource(ParticlePool::new(MAX_PARTICLES));
+
+        app.add_systems(OnEnter(GameState::Playing), release_all::<Particle>);
+        app.add_systems(OnEnter(GameState::MainMenu), release_all::<Particle>);
+        app.add_systems(
+            Update,
+            (emit, burst, update.after(emit).after(burst))
+                .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Warping))),
+        );
+    }
+}
+
+/// Most particles alive at once. Thrusters and explosions share these, so a
+/// big fight can thin out the exhaust but won't slow the game down.
+const MAX_PARTICLES: usize = 2000;
+
+/// How particles look and move when emitted
+#[derive(Clone)]
+pub struct ParticleSettings {
+    /// Seconds each particle lives for
+    pub lifetime: f32,
+    /// Direction particles are emitted in, relative to the emitter
+    pub direction: Vec2,
+    /// Radians either side of `direction` that particles can go
+    pub spread: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Particles are stretched along the way they're moving
+    pub size: Vec2,
+    pub start_color: Color,
+    pub end_color: Color,
+}
+
+impl ParticleSettings {
+    pub fn exhaust() -> Self {
+        Self {
+            lifetime: 0.4,
+            direction: Vec2::NEG_X,
+            spread: 0.3,
+            min_speed: 60.,
+            max_speed: 110.,
+            size: Vec2::new(4., 3.),
+            start_color: Color::rgb(1., 0.85, 0.3),
+            end_color: Color::rgba(1., 0.2, 0., 0.),
+        }
+    }
+
+    pub fn impact() -> Self {
+        Self {
+            lifetime: 0.2,
+            direction: Vec2::X,
+            spread: std::f32::consts::PI,
+            min_speed: 30.,
+            max_speed: 90.,
+            size: Vec2::new(3., 1.),
+            start_color: Color::YELLOW,
+            end_color: Color::rgba(1., 1., 1., 0.),
+        }
+    }
+
+    pub fn explosion(color: Color) -> Self {
+        Self {
+            lifetime: 0.5,
+            direction: Vec2::X,
+            spread: std::f32::consts::PI,
+            min_speed: 40.,
+            max_speed: 140.,
+            size: Vec2::new(3., 3.),
+            start_color: color,
+            end_color: Color::rgba(1., 0.5, 0., 0.),
+        }
+    }
+
+    pub fn warp() -> Self {
+        Self {
+            lifetime: 0.6,
+            direction: Vec2::NEG_X,
+            spread: 0.15,
+            min_speed: 300.,
+            max_speed: 500.,
+            size: Vec2::new(16., 1.),
+            start_color: Color::rgb(0.6, 0.9, 1.),
+            end_color: Color::rgba(0.2, 0.3, 1., 0.),
+        }
+    }
+}
+
+/// Emits particles continuously while active, from wherever the entity is
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub settings: ParticleSettings,
+    /// Particles per second
+    pub rate: f32,
+    pub active: bool,
+    /// Fractional particles carried over between frames
+    accumulator: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(settings: ParticleSettings, rate: f32) -> Self {
+        Self {
+            settings,
+            rate,
+            active: true,
+            accumulator: 0.,
+        }
+    }
+}
+
+/// Emits a number of particles at once, for one off effects like explosions
+#[derive(Event)]
+pub struct ParticleBurstEvent {
+    pub position: Vec2,
+    /// Rotation applied to the settings' direction
+    pub rotation: f32,
+    pub count: usize,
+    pub settings: ParticleSettings,
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    timer: Timer,
+    start_color: Color,
+    end_color: Color,
+}
+
+/// Exhaust and bursts both draw from the same particles
+type ParticlePool = Pool<Particle>;
+
+impl ParticlePool {
+    fn spawn(
+        &mut self,
+        commands: &mut Commands,
+        query: &mut Query<(&mut Particle, &mut Transform, &mut Visibility, &mut Sprite)>,
+        settings: &ParticleSettings,
+        position: Vec3,
+        rotation: f32,
+    ) {
+        let mut rng = thread_rng();
+
+        let angle = rotation
+            + settings.direction.y.atan2(settings.direction.x)
+            + rng.gen_range(-settings.spread..=settings.spread);
+        let velocity =
+            Vec2::from_angle(angle) * rng.gen_range(settings.min_speed..=settings.max_speed);
+
+        let particle = Particle {
+            velocity,
+            timer: Timer::from_seconds(
+                settings.lifetime * rng.gen_range(0.6..=1.0),
+                TimerMode::Once,
+            ),
+            start_color: settings.start_color,
+            end_color: settings.end_color,
+        };
+        let transform =
+            Transform::from_translation(position).with_rotation(Quat::from_rotation_z(angle));
+
+        match self.acquire() {
+            Some(Slot::Reuse(entity)) => {
+                if let Ok((mut old_particle, mut old_transform, mut visibility, mut sprite)) =
+                    query.get_mut(entity)
+                {
+                    *old_particle = particle;
+                    *old_transform = transform;
+                    *visibility = Visibility::Visible;
+                    sprite.color = settings.start_color;
+                    sprite.custom_size = Some(settings.size);
+                }
+            }
+            Some(Slot::Spawn) => {
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: settings.start_color,
+                            custom_size: Some(settings.size),
+                            ..default()
+                        },
+                        transform,
+                        ..default()
+                    },
+                    particle,
+                ));
+            }
+            None => {}
+        }
+    }
+}
+
+fn emit(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<ParticlePool>,
+    mut emitter_query: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+    mut query: Query<(&mut Particle, &mut Transform, &mut Visibility, &mut Sprite)>,
+) {
+    for (mut emitter, global_transform) in emitter_query.iter_mut() {
+        if !emitter.active {
+            emitter.accumulator = 0.;
+            continue;
+        }
+
+        emitter.accumulator += emitter.rate * time.delta_seconds();
+
+        // Particles start out at the emitter's depth, so that exhaust stays
+        // behind the ship
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let rotation = rotation.to_euler(EulerRot::XYZ).2;
+
+        while emitter.accumulator >= 1. {
+            emitter.accumulator -= 1.;
+
+            pool.spawn(
+                &mut commands,
+                &mut query,
+                &emitter.settings,
+                translation,
+                rotation,
+            );
+        }
+    }
+}
+
+fn burst(
+    mut commands: Commands,
+    mut events: EventReader<ParticleBurstEvent>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(&mut Particle, &mut Transform, &mut Visibility, &mut Sprite)>,
+) {
+    for event in events.iter() {
+        for _ in 0..event.count {
+            pool.spawn(
+                &mut commands,
+                &mut query,
+                &event.settings,
+                event.position.extend(layer::EFFECT),
+                event.rotation,
+            );
+        }
+    }
+}
+
+/// Moves particles and blends their colour over their life, returning them to
+/// the pool when done
+fn update(
+    time: Res<Time>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Visibility,
+        &mut Sprite,
+    )>,
+) {
+    for (entity, mut particle, mut transform, mut visibility, mut sprite) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        particle.timer.tick(time.delta());
+        if particle.timer.finished() {
+            *visibility = Visibility::Hidden;
+            pool.release(entity);
+            continue;
+        }
+
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+
+        let start = Vec4::from(particle.start_color.as_rgba_f32());
+        let end = Vec4::from(particle.end_color.as_rgba_f32());
+        sprite.color = Color::from(start.lerp(end, particle.timer.percent()));
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    controls::{ControlsMenu, OpenControlsEvent},
+    shop::{self, ShopMenu},
+    Action, Fonts, GameState,
+};
+
+pub struct PausePlugin;
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>();
+
+        app.add_systems(OnEnter(PauseState::Paused), pause);
+        app.add_systems(OnExit(PauseState::Paused), resume);
+        app.add_systems(
+            Update,
+            toggle
+                .after(shop::toggle)
+                .run_if(in_state(GameState::Playing)),
+        );
+        app.add_systems(Update, click.run_if(in_state(PauseState::Paused)));
+    }
+}
+
+/// Whether the game is paused, alongside `GameState`.
+///
+/// Pausing stops the clock, which freezes timers and the fixed timestep
+/// physics, and disables the player's actions.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Options,
+    Restart,
+    /// Saves the run and leaves it, so that it can be continued later
+    MainMenu,
+}
+
+impl PauseButton {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Resume => "Resume",
+            Self::Options => "Options",
+            Self::Restart => "Restart Run",
+            Self::MainMenu => "Main Menu",
+        }
+    }
+}
+
+fn toggle(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    controls_menu: Res<ControlsMenu>,
+    shop_menu: Res<ShopMenu>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    // Escape belongs to the controls screen or shop while they're open
+    if controls_menu.is_open() || shop_menu.is_open() {
+        return;
+    }
+
+    let pressed = keys.just_pressed(KeyCode::Escape)
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::Start);
+
+    if !pressed {
+        return;
+    }
+
+    next_state.set(match state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+fn pause(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    fonts: Res<Fonts>,
+) {
+    time.pause();
+    toggle_actions.enabled = false;
+
+    let text_style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 20.,
+        color: Color::BEIGE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            PauseMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Paused",
+                    TextStyle {
+                        font_size: 40.,
+                        ..text_style.clone()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(20.)),
+                    ..default()
+                }),
+            );
+
+            for button in [
+                PauseButton::Resume,
+                PauseButton::Options,
+                PauseButton::Restart,
+                PauseButton::MainMenu,
+            ] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(240.),
+                                margin: UiRect::all(Val::Px(4.)),
+                                padding: UiRect::all(Val::Px(8.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(button.label(), text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn resume(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    query: Query<Entity, With<PauseMenu>>,
+) {
+    time.unpause();
+    toggle_actions.enabled = true;
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn click(
+    query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    controls_menu: Res<ControlsMenu>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut open_controls: EventWriter<OpenControlsEvent>,
+) {
+    // The controls screen sits on top of this menu
+    if controls_menu.is_open() {
+        return;
+    }
+
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PauseButton::Resume => next_pause_state.set(PauseState::Running),
+            PauseButton::Options => open_controls.send(OpenControlsEvent),
+            PauseButton::Restart => {
+                next_pause_state.set(PauseState::Running);
+                next_game_state.set(GameState::Restarting);
+            }
+            PauseButton::MainMenu => {
+                next_pause_state.set(PauseState::Running);
+                next_game_state.set(GameState::MainMenu);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+
+use crate::{MovementSet, PhysicsSet};
+
+/// Length of a physics step, in seconds
+pub const TIMESTEP: f32 = 1. / 60.;
+
+pub struct PhysicsPlugin;
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(TIMESTEP));
+
+        app.add_systems(
+            FixedUpdate,
+            (restore.before(PhysicsSet), snapshot.after(PhysicsSet)),
+        );
+        app.add_systems(Update, interpolate.in_set(MovementSet));
+    }
+}
+
+/// Smooths rendering of an entity moved by the fixed timestep physics
+///
+/// Physics systems see the entity's `Transform` as it was at the end of the
+/// last physics step. Everything else sees it interpolated between the last
+/// two steps.
+#[derive(Component)]
+pub struct Interpolated {
+    previous: (Vec3, Quat),
+    current: (Vec3, Quat),
+}
+
+impl Interpolated {
+    pub fn new(transform: &Transform) -> Self {
+        let state = (transform.translation, transform.rotation);
+
+        Self {
+            previous: state,
+            current: state,
+        }
+    }
+
+    /// Moves the entity without smoothing, e.g. when it is reset between
+    /// systems
+    pub fn teleport(&mut self, transform: &mut Transform, translation: Vec3) {
+        transform.translation = translation;
+        *self = Self::new(transform);
+    }
+}
+
+/// Undoes interpolation so that the physics step starts from where the last
+/// one finished
+fn restore(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in query.iter_mut() {
+        (transform.translation, transform.rotation) = interpolated.current;
+    }
+}
+
+fn snapshot(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = interpolated.current;
+        interpolated.current = (transform.translation, transform.rotation);
+    }
+}
+
+pub fn interpolate(fixed_time: Res<FixedTime>, mut query: Query<(&mut Transform, &Interpolated)>) {
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).min(1.);
+
+    for (mut transform, interpolated) in query.iter_mut() {
+        let (previous_translation, previous_rotation) = interpolated.previous;
+        let (current_translation, current_rotation) = interpolated.current;
+
+        transform.translation = previous_translation.lerp(current_translation, alpha);
+        transform.rotation = previous_rotation.slerp(current_rotation, alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        combat_feedback::DeathEvent,
+        enemy::{Enemy, EnemyPlugin},
+        fuel::SpawnFuelPelletEvent,
+        gravity::{Gravity, GravityPlugin, Mass},
+        run::RunSeed,
+        shield::DamageEvent,
+        GameState, Player, Velocity,
+    };
+
+    /// A system with the player drifting past a planet, seen through a
+    /// 1280x720 window
+    fn app(seed: u64) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_state::<GameState>()
+            .add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<SpawnFuelPelletEvent>()
+            .insert_resource(RunSeed { seed, ..default() })
+            .add_plugins((PhysicsPlugin, GravityPlugin, EnemyPlugin))
+            .add_systems(
+                FixedUpdate,
+                crate::movement
+                    .run_if(in_state(GameState::Playing))
+                    .in_set(PhysicsSet),
+            );
+
+        app.world.resource_mut::<Gravity>().enabled = true;
+
+        let transform = Transform::default();
+        app.world.spawn((
+            Player,
+            transform,
+            Velocity(Vec2::new(40., 10.)),
+            Interpolated::new(&transform),
+        ));
+        app.world
+            .spawn((Transform::from_xyz(600., 0., 0.), Mass(500_000.)));
+        app.world.spawn(OrthographicProjection {
+            area: Rect::new(-640., -360., 640., 360.),
+            ..default()
+        });
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.world.run_schedule(StateTransition);
+
+        app
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        for _ in 0..(seconds / TIMESTEP).round() as usize {
+            app.world.run_schedule(FixedUpdate);
+        }
+    }
+
+    fn enemies(app: &mut App) -> Vec<(Entity, Vec2)> {
+        app.world
+            .query_filtered::<(Entity, &Transform), With<Enemy>>()
+            .iter(&app.world)
+            .map(|(entity, transform)| (entity, transform.translation.truncate()))
+            .collect()
+    }
+
+    /// Where the enemies are after the first 30 seconds
+    fn simulate(seed: u64) -> Vec<Vec2> {
+        let mut app = app(seed);
+        step(&mut app, 30.);
+
+        enemies(&mut app).into_iter().map(|(_, pos)| pos).collect()
+    }
+
+    #[test]
+    fn replays_match() {
+        let first = simulate(7);
+
+        assert!(!first.is_empty());
+        assert_eq!(first, simulate(7));
+        assert_ne!(first, simulate(8));
+    }
+
+    #[test]
+    fn spawned_enemies_survive() {
+        let mut app = app(7);
+
+        let mut seen = vec![];
+        let mut new: Vec<Entity> = vec![];
+
+        // Long enough for several spawns, all around the edge of the view
+        for _ in 0..(10. / TIMESTEP) as usize {
+            step(&mut app, TIMESTEP);
+            let alive: Vec<Entity> = enemies(&mut app).into_iter().map(|(e, _)| e).collect();
+
+            for entity in &new {
+                assert!(alive.contains(entity), "removed the step after spawning");
+            }
+
+            new = alive
+                .into_iter()
+                .filter(|entity| !seen.contains(entity))
+                .collect();
+            seen.extend(new.iter().copied());
+        }
+
+        assert!(seen.len() >= 4);
+    }
+}
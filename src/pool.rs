@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+/// Entities with the component `T` that are hidden when finished with and
+/// reused, rather than being despawned. Stops growing once `max` entities have
+/// been spawned.
+#[derive(Resource)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    total: usize,
+    max: usize,
+    marker: PhantomData<T>,
+}
+
+/// Where the next pooled entity comes from
+pub enum Slot {
+    /// A hidden entity to show again
+    Reuse(Entity),
+    /// There's room in the pool for another entity
+    Spawn,
+}
+
+impl<T: Component> Pool<T> {
+    pub fn new(max: usize) -> Self {
+        Self {
+            free: vec![],
+            total: 0,
+            max,
+            marker: PhantomData,
+        }
+    }
+
+    /// A free entity if there is one, otherwise room to spawn another. `None`
+    /// when the pool is full, in which case the caller should drop whatever it
+    /// was going to show.
+    pub fn acquire(&mut self) -> Option<Slot> {
+        if let Some(entity) = self.free.pop() {
+            Some(Slot::Reuse(entity))
+        } else if self.total < self.max {
+            self.total += 1;
+            Some(Slot::Spawn)
+        } else {
+            None
+        }
+    }
+
+    /// Hands back an entity that has been hidden
+    pub fn release(&mut self, entity: Entity) {
+        self.free.push(entity);
+    }
+}
+
+/// Hides and frees every `T` still showing, so that nothing carries over from
+/// before a warp or restart
+pub fn release_all<T: Component>(
+    mut pool: ResMut<Pool<T>>,
+    mut query: Query<(Entity, &mut Visibility), With<T>>,
+) {
+    for (entity, mut visibility) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        *visibility = Visibility::Hidden;
+        pool.release(entity);
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{run::RunSeed, Credits, GameState, Player};
+
+pub struct ProfilePlugin;
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load());
+
+        app.add_systems(OnEnter(GameState::Playing), record);
+    }
+}
+
+/// Where the profile is saved, relative to the working directory
+const PROFILE_PATH: &str = "profile.ron";
+
+/// The player's progress across all runs, used for unlocks
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// Counting the first system of a run as 1
+    pub furthest_system: u32,
+    pub most_credits: u32,
+}
+
+impl Profile {
+    fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(PROFILE_PATH) else {
+            return Self::default();
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring invalid {}: {}", PROFILE_PATH, err);
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                std::fs::write(PROFILE_PATH, contents).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", PROFILE_PATH, err);
+        }
+    }
+}
+
+/// Records progress upon entering each system
+fn record(seed: Res<RunSeed>, query: Query<&Credits, With<Player>>, mut profile: ResMut<Profile>) {
+    let credits = query.single();
+
+    let furthest_system = profile.furthest_system.max(seed.system + 1);
+    let most_credits = profile.most_credits.max(credits.0);
+
+    if furthest_system == profile.furthest_system && most_credits == profile.most_credits {
+        return;
+    }
+
+    profile.furthest_system = furthest_system;
+    profile.most_credits = most_credits;
+    profile.save();
+}
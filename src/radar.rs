@@ -0,0 +1,283 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_spatial::{kdtree::KDTree2, SpatialAccess};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    commodity::Commodity,
+    fuel::FuelPellet,
+    layer,
+    scanner::{ScanState, Scanner},
+    warp_node::WarpNode,
+    Action, DespawnOnRestart, Fonts, GameState, MovementSet, Planet, Player, SpatialIndex,
+};
+
+pub struct RadarPlugin;
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RadarSettings>();
+
+        app.add_systems(OnEnter(GameState::Playing), setup);
+        app.add_systems(
+            Update,
+            (switch, update.after(switch).after(MovementSet)).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Radius of the radar on screen, in pixels
+const RADIUS: f32 = 70.;
+/// Gap between the radar and the corner of the screen, in pixels
+const MARGIN: f32 = 15.;
+const BLIP_SIZE: f32 = 4.;
+/// Most things plotted at once. Anything past this is left off.
+const MAX_BLIPS: usize = 128;
+/// World distances the radar can cover, switched between by the player
+const RANGES: [f32; 3] = [1000., 2500., 5000.];
+
+const ENEMY_COLOR: Color = Color::RED;
+const PELLET_COLOR: Color = Color::GREEN;
+const COMMODITY_COLOR: Color = Color::BEIGE;
+const WARP_NODE_COLOR: Color = Color::ORANGE;
+const PLANET_COLOR: Color = Color::CYAN;
+
+/// Which kinds of things the radar shows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RadarFilter {
+    #[default]
+    All,
+    /// Enemies only
+    Threats,
+    /// Fuel pellets and revealed commodities
+    Resources,
+    /// Planets and revealed warp nodes
+    Navigation,
+}
+
+impl RadarFilter {
+    fn next(self) -> Self {
+        match self {
+            Self::All => Self::Threats,
+            Self::Threats => Self::Resources,
+            Self::Resources => Self::Navigation,
+            Self::Navigation => Self::All,
+        }
+    }
+
+    fn shows_threats(self) -> bool {
+        matches!(self, Self::All | Self::Threats)
+    }
+
+    fn shows_resources(self) -> bool {
+        matches!(self, Self::All | Self::Resources)
+    }
+
+    fn shows_navigation(self) -> bool {
+        matches!(self, Self::All | Self::Navigation)
+    }
+}
+
+/// Kept across warps and restarts
+#[derive(Resource, Default)]
+pub struct RadarSettings {
+    /// Index into `RANGES`
+    pub range: usize,
+    pub filter: RadarFilter,
+}
+
+impl RadarSettings {
+    pub fn range(&self) -> f32 {
+        RANGES[self.range]
+    }
+}
+
+/// Follows the camera, sitting in the bottom left corner of the screen
+#[derive(Component)]
+struct Radar;
+/// A dot on the radar. Hidden when not in use.
+#[derive(Component)]
+struct RadarBlip;
+#[derive(Component)]
+struct RadarLabel;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    camera_query: Query<Entity, With<Camera>>,
+    fonts: Res<Fonts>,
+) {
+    let camera = camera_query.single();
+
+    let radar = commands
+        .spawn((SpatialBundle::default(), Radar, DespawnOnRestart))
+        .with_children(|parent| {
+            parent.spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(RADIUS + 1.).into()).into(),
+                material: materials.add(Color::rgba(0.5, 0.5, 0.5, 0.5).into()),
+                ..default()
+            });
+            parent.spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(RADIUS).into()).into(),
+                material: materials.add(Color::rgba(0., 0.05, 0.1, 0.8).into()),
+                transform: Transform::from_xyz(0., 0., 0.1),
+                ..default()
+            });
+
+            // The player, always in the middle
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::WHITE,
+                    custom_size: Some(Vec2::splat(BLIP_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., 0.3),
+                ..default()
+            });
+
+            for _ in 0..MAX_BLIPS {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(BLIP_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0., 0., 0.2),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    RadarBlip,
+                ));
+            }
+
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: fonts.main.clone(),
+                            font_size: 14.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., RADIUS + 10., 0.3),
+                    ..default()
+                },
+                RadarLabel,
+            ));
+        })
+        .id();
+
+    commands.entity(camera).add_child(radar);
+}
+
+fn switch(query: Query<&ActionState<Action>, With<Player>>, mut settings: ResMut<RadarSettings>) {
+    let action_state = query.single();
+
+    if action_state.just_pressed(Action::CycleRadarRange) {
+        settings.range = (settings.range + 1) % RANGES.len();
+    }
+    if action_state.just_pressed(Action::CycleRadarFilter) {
+        settings.filter = settings.filter.next();
+    }
+}
+
+/// Keeps the radar in the corner of the view and plots everything in range
+fn update(
+    settings: Res<RadarSettings>,
+    scanner: Res<Scanner>,
+    tree: Res<KDTree2<SpatialIndex>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), (With<Camera>, Without<Radar>)>,
+    mut radar_query: Query<&mut Transform, With<Radar>>,
+    mut blip_query: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        (With<RadarBlip>, Without<Radar>, Without<Camera>),
+    >,
+    mut label_query: Query<&mut Text, With<RadarLabel>>,
+    pellet_query: Query<&GlobalTransform, With<FuelPellet>>,
+    commodity_query: Query<(Entity, &GlobalTransform), With<Commodity>>,
+    warp_node_query: Query<(Entity, &GlobalTransform), With<WarpNode>>,
+    planet_query: Query<&GlobalTransform, With<Planet>>,
+) {
+    let player = player_query.single().translation().truncate();
+    let (camera, projection) = camera_query.single();
+
+    // The radar is a child of the camera, so it only needs placing relative
+    // to the view. Scaling with zoom keeps it the same size on screen, and
+    // undoing the camera's rotation stops screen shake from turning it, so
+    // that blips stay lined up with the world.
+    let Ok(mut radar) = radar_query.get_single_mut() else {
+        return;
+    };
+    let offset = -projection.area.half_size() + Vec2::splat(RADIUS + MARGIN) * projection.scale;
+    radar.translation = offset.extend(layer::UI - camera.translation.z);
+    radar.rotation = camera.rotation.inverse();
+    radar.scale = Vec3::new(projection.scale, projection.scale, 1.);
+
+    let range = settings.range();
+    let filter = settings.filter;
+
+    // Blips are handed out in this order, so the few navigation and resource
+    // targets are always drawn even when a swarm of enemies uses up the rest
+    let mut targets: Vec<(Vec2, Color)> = Vec::new();
+
+    if filter.shows_navigation() {
+        targets.extend(
+            warp_node_query
+                .iter()
+                .filter(|(entity, _)| scanner.state(*entity) == Some(ScanState::Revealed))
+                .map(|(_, transform)| (transform.translation().truncate(), WARP_NODE_COLOR)),
+        );
+        targets.extend(
+            planet_query
+                .iter()
+                .map(|transform| (transform.translation().truncate(), PLANET_COLOR)),
+        );
+    }
+
+    if filter.shows_resources() {
+        targets.extend(
+            commodity_query
+                .iter()
+                .filter(|(entity, _)| scanner.state(*entity) == Some(ScanState::Revealed))
+                .map(|(_, transform)| (transform.translation().truncate(), COMMODITY_COLOR)),
+        );
+        targets.extend(
+            pellet_query
+                .iter()
+                .map(|transform| (transform.translation().truncate(), PELLET_COLOR)),
+        );
+    }
+
+    if filter.shows_threats() {
+        // Nearest first, so that the closest threats are the ones shown
+        let mut enemies = tree.within_distance(player, range);
+        enemies.sort_by(|(a, _), (b, _)| {
+            a.distance_squared(player)
+                .total_cmp(&b.distance_squared(player))
+        });
+
+        targets.extend(enemies.into_iter().map(|(pos, _)| (pos, ENEMY_COLOR)));
+    }
+
+    let mut in_range = targets
+        .into_iter()
+        .map(|(pos, color)| (pos - player, color))
+        .filter(|(diff, _)| diff.length() <= range);
+
+    for (mut transform, mut sprite, mut visibility) in blip_query.iter_mut() {
+        let Some((diff, color)) = in_range.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        transform.translation.x = diff.x / range * RADIUS;
+        transform.translation.y = diff.y / range * RADIUS;
+        sprite.color = color;
+        *visibility = Visibility::Visible;
+    }
+
+    if let Ok(mut text) = label_query.get_single_mut() {
+        text.sections[0].value = format!("{:.1}Mm {:?}", range / 1000., filter);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{loadout::Loadout, warp_node::WarpedTo, Credits, FuelTank, GameState, Health, Player};
+
+pub struct RunPlugin;
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSetup>();
+        app.init_resource::<RunSeed>();
+
+        app.add_systems(OnEnter(GameState::Playing), save);
+        app.add_systems(
+            OnTransition {
+                from: GameState::Playing,
+                to: GameState::MainMenu,
+            },
+            save,
+        );
+        app.add_systems(OnExit(GameState::Warping), next_system);
+    }
+}
+
+/// Where the run in progress is saved, relative to the working directory
+const SAVE_PATH: &str = "run.ron";
+
+/// Choices made before starting a run
+#[derive(Resource, Default)]
+pub struct RunSetup {
+    /// Index into `Hulls`
+    pub hull: usize,
+    /// Random if not given
+    pub seed: Option<u64>,
+    /// A saved run to pick up from instead of starting fresh
+    pub saved: Option<SavedRun>,
+}
+
+/// Seeds the random generation of each system in a run, so that a run can be
+/// replayed or continued
+#[derive(Resource, Default)]
+pub struct RunSeed {
+    pub seed: u64,
+    /// How many systems the player has visited
+    pub system: u32,
+    /// Where the current system is in the galaxy, in light years
+    pub position: Vec2,
+}
+
+impl RunSeed {
+    /// A generator for the current system. Each `purpose` gets its own stream
+    /// so that the results don't depend on the order systems run in.
+    ///
+    /// Built only from fixed algorithms, unlike `DefaultHasher` and `StdRng`,
+    /// so that a saved or shared seed makes the same systems after a toolchain
+    /// or dependency update.
+    pub fn rng(&self, purpose: &str) -> ChaCha8Rng {
+        let mut state = splitmix64(self.seed);
+        state = splitmix64(state ^ u64::from(self.system));
+        state = splitmix64(state ^ fnv1a(purpose.as_bytes()));
+
+        ChaCha8Rng::seed_from_u64(state)
+    }
+}
+
+/// Scrambles a 64 bit value, for mixing seeds together
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// 64 bit FNV-1a hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// The state of a run at the start of a system, or when it was left for the
+/// main menu. Continuing starts the system over.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+    pub seed: u64,
+    pub system: u32,
+    /// Galaxy coordinates of the system, missing from older saves
+    #[serde(default)]
+    pub position: [f32; 2],
+    pub hull: usize,
+    pub fuel: u32,
+    pub credits: u32,
+    pub health: f32,
+    /// What's installed in each of the hull's slots
+    pub modules: Vec<Option<usize>>,
+}
+
+impl SavedRun {
+    pub fn load() -> Option<Self> {
+        let contents = std::fs::read_to_string(SAVE_PATH).ok()?;
+
+        ron::from_str(&contents)
+            .map_err(|err| warn!("Ignoring invalid {}: {}", SAVE_PATH, err))
+            .ok()
+    }
+}
+
+/// Sets up the seed for a new or continued run
+pub fn start(mut setup: ResMut<RunSetup>, mut seed: ResMut<RunSeed>) {
+    *seed = match &setup.saved {
+        Some(saved) => RunSeed {
+            seed: saved.seed,
+            system: saved.system,
+            position: Vec2::from(saved.position),
+        },
+        None => RunSeed {
+            seed: setup.seed.unwrap_or_else(|| thread_rng().gen()),
+            system: 0,
+            position: Vec2::ZERO,
+        },
+    };
+
+    // Restarting replays the same seed
+    setup.seed = Some(seed.seed);
+}
+
+fn next_system(mut seed: ResMut<RunSeed>, warped_to: Option<Res<WarpedTo>>) {
+    seed.system += 1;
+
+    if let Some(warped_to) = warped_to {
+        seed.position = warped_to.position;
+    }
+}
+
+fn save(
+    setup: Res<RunSetup>,
+    seed: Res<RunSeed>,
+    query: Query<(&FuelTank, &Credits, &Health, &Loadout), With<Player>>,
+) {
+    let (fuel_tank, credits, health, loadout) = query.single();
+
+    let saved = SavedRun {
+        seed: seed.seed,
+        system: seed.system,
+        position: seed.position.to_array(),
+        hull: setup.hull,
+        fuel: fuel_tank.current,
+        credits: credits.0,
+        health: health.current,
+        modules: loadout.0.iter().map(|slot| slot.module).collect(),
+    };
+
+    let result = ron::ser::to_string_pretty(&saved, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| std::fs::write(SAVE_PATH, contents).map_err(|err| err.to_string()));
+
+    if let Err(err) = result {
+        warn!("Failed to save {}: {}", SAVE_PATH, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Saved runs and shared seeds rely on these never changing
+    #[test]
+    fn rng_is_stable() {
+        let seed = RunSeed {
+            seed: 42,
+            system: 3,
+            ..default()
+        };
+
+        assert_eq!(seed.rng("celestial").gen::<u64>(), 14527344458900167734);
+        assert_ne!(
+            seed.rng("celestial").gen::<u64>(),
+            seed.rng("enemy").gen::<u64>()
+        );
+    }
+}
@@ -1,9 +1,14 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::*;
 
 use crate::{
-    commodity::Commodity,
+    commodity::CommodityPickedUpEvent,
     direction_indicator::{DirectionIndicator, DirectionIndicatorSettings},
-    DespawnOnRestart, GameState, Player,
+    enemy::Enemy,
+    loadout::{Loadout, SlotKind, MODULES},
+    Action, Credits, DespawnOnRestart, GameState, Planet, Player,
 };
 
 pub struct ScannerPlugin;
@@ -11,32 +16,366 @@ impl Plugin for ScannerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Scanner>();
         app.add_systems(OnExit(GameState::Warping), reset);
+        app.add_systems(OnExit(GameState::Restarting), reset);
         app.add_systems(
             Update,
-            (proximity, update, unpause).run_if(in_state(GameState::Playing)),
+            (
+                apply_module,
+                switch_mode,
+                upgrade,
+                (register, track_pickups, track_despawns)
+                    .before(update)
+                    .before(proximity)
+                    .before(ping),
+                proximity,
+                update.after(apply_module),
+                ping.after(apply_module),
+            )
+                .run_if(in_state(GameState::Playing)),
         );
     }
 }
 
+/// How a scanner module performs. Each module's stats live with the rest of
+/// its definition in [`crate::loadout::MODULES`].
+#[derive(Clone, Copy, PartialEq)]
+pub struct ScannerStats {
+    /// Seconds between reveals
+    pub scan_time: f32,
+    /// Maximum distance from the player at which targets can be revealed
+    pub range: f32,
+    pub ping_radius: f32,
+    /// Seconds before the ping can be used again
+    pub ping_cooldown: f32,
+}
+
+/// What a ship scans with when no scanner module is installed
+const BUILT_IN_SENSORS: ScannerStats = ScannerStats {
+    scan_time: 35.,
+    range: 3500.,
+    ping_radius: 800.,
+    ping_cooldown: 60.,
+};
+
+/// The scanner a ship scans with, set from its loadout
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct ScannerModule {
+    pub name: &'static str,
+    pub stats: ScannerStats,
+}
+
+impl Default for ScannerModule {
+    fn default() -> Self {
+        Self {
+            name: "Built-in Sensors",
+            stats: BUILT_IN_SENSORS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScanMode {
+    #[default]
+    Commodities,
+    WarpNodes,
+    Enemies,
+}
+
+impl ScanMode {
+    fn next(self) -> Self {
+        match self {
+            Self::Commodities => Self::WarpNodes,
+            Self::WarpNodes => Self::Enemies,
+            Self::Enemies => Self::Commodities,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanTargetKind {
+    Commodity,
+    WarpNode,
+    PointOfInterest,
+}
+
+/// Makes an entity discoverable by the scanner
+///
+/// The entity also needs a `DirectionIndicatorSettings`, which is used for
+/// the indicator that is spawned when it is revealed.
+#[derive(Component, Clone)]
+pub struct Scannable {
+    pub kind: ScanTargetKind,
+    /// Among targets of the same kind, higher priorities are revealed first
+    pub priority: u32,
+    /// The scanner's range and ping radius are divided by this when searching
+    /// for this target
+    pub difficulty: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanState {
+    /// Not yet found by the scanner
+    Hidden,
+    /// Has a `DirectionIndicator` pointing at it
+    Revealed,
+    /// Picked up by the player or otherwise gone from the system
+    Collected,
+}
+
+pub struct ScanTarget {
+    pub entity: Entity,
+    pub kind: ScanTargetKind,
+    pub state: ScanState,
+}
+
 #[derive(Resource)]
 pub struct Scanner {
     pub timer: Timer,
-    pub commodities: Vec<Entity>,
-    pub warp_nodes: Vec<Entity>,
+    pub ping_timer: Timer,
+    pub range: f32,
+    pub ping_radius: f32,
+    pub mode: ScanMode,
+    /// While set, the scanner neither scans nor pings, e.g. during a solar flare
+    pub disabled: bool,
+    targets: Vec<ScanTarget>,
 }
 
-impl Default for Scanner {
-    fn default() -> Self {
+impl Scanner {
+    fn new(module: &ScannerModule, mode: ScanMode) -> Self {
+        let stats = module.stats;
+
+        // The ping starts out ready to use
+        let mut ping_timer = Timer::from_seconds(stats.ping_cooldown, TimerMode::Once);
+        ping_timer.tick(ping_timer.duration());
+
         Self {
-            timer: Timer::from_seconds(35., TimerMode::Repeating),
-            commodities: Vec::new(),
-            warp_nodes: Vec::new(),
+            timer: Timer::from_seconds(stats.scan_time, TimerMode::Repeating),
+            ping_timer,
+            range: stats.range,
+            ping_radius: stats.ping_radius,
+            mode,
+            disabled: false,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Adds an entity to the queue of things the scanner will reveal
+    fn register(&mut self, entity: Entity, kind: ScanTargetKind) {
+        self.targets.push(ScanTarget {
+            entity,
+            kind,
+            state: ScanState::Hidden,
+        });
+        self.sync_timer();
+    }
+
+    pub fn state(&self, entity: Entity) -> Option<ScanState> {
+        self.targets
+            .iter()
+            .find(|t| t.entity == entity)
+            .map(|t| t.state)
+    }
+
+    pub fn count(&self, kind: ScanTargetKind, state: ScanState) -> usize {
+        self.targets
+            .iter()
+            .filter(|t| t.kind == kind && t.state == state)
+            .count()
+    }
+
+    fn hidden(&self, kind: ScanTargetKind) -> impl Iterator<Item = Entity> + '_ {
+        self.targets
+            .iter()
+            .filter(move |t| t.kind == kind && t.state == ScanState::Hidden)
+            .map(|t| t.entity)
+    }
+
+    fn all_hidden(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.targets
+            .iter()
+            .filter(|t| t.state == ScanState::Hidden)
+            .map(|t| t.entity)
+    }
+
+    fn set_state(&mut self, entity: Entity, state: ScanState) {
+        let Some(target) = self.targets.iter_mut().find(|t| t.entity == entity) else {
+            return;
+        };
+
+        target.state = state;
+        self.sync_timer();
+    }
+
+    /// The kinds of target the next timed scan should search for, in order
+    fn scan_order(&self) -> Vec<ScanTargetKind> {
+        use ScanTargetKind::*;
+
+        let order: &[ScanTargetKind] = match self.mode {
+            ScanMode::Enemies => &[],
+            ScanMode::WarpNodes => &[WarpNode, Commodity, PointOfInterest],
+            ScanMode::Commodities => &[Commodity, PointOfInterest, WarpNode],
+        };
+
+        order
+            .iter()
+            .copied()
+            .filter(|kind| {
+                // Warp nodes are only searched for after every commodity is collected
+                if self.mode == ScanMode::Commodities
+                    && *kind == WarpNode
+                    && (self.count(Commodity, ScanState::Hidden) > 0
+                        || self.count(Commodity, ScanState::Revealed) > 0)
+                {
+                    return false;
+                }
+
+                self.count(*kind, ScanState::Hidden) > 0
+            })
+            .collect()
+    }
+
+    /// Runs the timer only while there is something for it to reveal
+    fn sync_timer(&mut self) {
+        if self.mode == ScanMode::Enemies || !self.scan_order().is_empty() {
+            self.timer.unpause();
+        } else if !self.timer.paused() {
+            self.timer.reset();
+            self.timer.pause();
         }
     }
 }
 
-pub fn reset(mut scanner: ResMut<Scanner>) {
-    *scanner = Scanner::default();
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new(&ScannerModule::default(), ScanMode::default())
+    }
+}
+
+pub fn reset(mut scanner: ResMut<Scanner>, query: Query<&ScannerModule, With<Player>>) {
+    let mode = scanner.mode;
+    *scanner = Scanner::new(query.single(), mode);
+}
+
+fn reveal(
+    commands: &mut Commands,
+    scanner: &mut Scanner,
+    entity: Entity,
+    settings: DirectionIndicatorSettings,
+) {
+    commands.spawn((
+        DirectionIndicator {
+            target: entity,
+            settings,
+        },
+        DespawnOnRestart,
+    ));
+
+    scanner.set_state(entity, ScanState::Revealed);
+}
+
+/// Enemies within `range` of `origin` that don't have an indicator yet, and
+/// their squared distance from `origin`
+fn unrevealed_enemies(
+    enemy_query: &Query<(Entity, &Transform), With<Enemy>>,
+    indicator_query: &Query<&DirectionIndicator>,
+    origin: Vec3,
+    range: f32,
+) -> Vec<(Entity, f32)> {
+    let revealed: HashSet<Entity> = indicator_query.iter().map(|i| i.target).collect();
+
+    enemy_query
+        .iter()
+        .filter(|(entity, _)| !revealed.contains(entity))
+        .map(|(entity, transform)| (entity, transform.translation.distance_squared(origin)))
+        .filter(|(_, dist)| *dist <= range * range)
+        .collect()
+}
+
+fn enemy_indicator_settings() -> DirectionIndicatorSettings {
+    DirectionIndicatorSettings {
+        color: Color::PURPLE,
+        label: None,
+    }
+}
+
+/// Updates the scanner's timers and range when a new module is installed
+fn apply_module(
+    query: Query<&ScannerModule, (Changed<ScannerModule>, With<Player>)>,
+    mut scanner: ResMut<Scanner>,
+) {
+    for module in query.iter() {
+        let stats = module.stats;
+
+        let pct = scanner.timer.percent();
+        scanner
+            .timer
+            .set_duration(Duration::from_secs_f32(stats.scan_time));
+        let elapsed = scanner.timer.duration().mul_f32(pct);
+        scanner.timer.set_elapsed(elapsed);
+
+        scanner
+            .ping_timer
+            .set_duration(Duration::from_secs_f32(stats.ping_cooldown));
+
+        scanner.range = stats.range;
+        scanner.ping_radius = stats.ping_radius;
+    }
+}
+
+fn switch_mode(query: Query<&ActionState<Action>, With<Player>>, mut scanner: ResMut<Scanner>) {
+    let action_state = query.single();
+
+    if action_state.just_pressed(Action::CycleScanMode) {
+        scanner.mode = scanner.mode.next();
+        scanner.sync_timer();
+    }
+}
+
+/// Buys the next scanner module while the player is near a planet
+fn upgrade(
+    mut query: Query<(&ActionState<Action>, &Transform, &mut Loadout, &mut Credits), With<Player>>,
+    planet_query: Query<&Transform, With<Planet>>,
+) {
+    let (action_state, player_transform, mut loadout, mut credits) = query.single_mut();
+
+    if !action_state.just_pressed(Action::UpgradeScanner) {
+        return;
+    }
+
+    let near_planet = planet_query.iter().any(|planet| {
+        planet
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            < 150.
+    });
+    if !near_planet {
+        return;
+    }
+
+    // Scanners are listed from worst to best
+    let installed = loadout
+        .slot_with(SlotKind::Scanner)
+        .and_then(|slot| loadout.0[slot].module);
+    let Some(next) = MODULES
+        .iter()
+        .enumerate()
+        .skip(installed.map_or(0, |module| module + 1))
+        .find(|(_, module)| module.slot == SlotKind::Scanner)
+        .map(|(index, _)| index)
+    else {
+        return;
+    };
+
+    let Some((slot, price)) = loadout.quote(next) else {
+        return;
+    };
+    if credits.0 < price {
+        return;
+    }
+
+    credits.0 -= price;
+    loadout.install(slot, next);
 }
 
 pub fn update(
@@ -44,8 +383,14 @@ pub fn update(
     time: Res<Time>,
     mut scanner: ResMut<Scanner>,
     player_query: Query<&Transform, With<Player>>,
-    target_query: Query<(Entity, &Transform, &DirectionIndicatorSettings)>,
+    target_query: Query<(Entity, &Transform, &Scannable, &DirectionIndicatorSettings)>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    indicator_query: Query<&DirectionIndicator>,
 ) {
+    if scanner.disabled {
+        return;
+    }
+
     scanner.timer.tick(time.delta());
     if !scanner.timer.just_finished() {
         return;
@@ -53,62 +398,135 @@ pub fn update(
 
     let player_transform = player_query.single();
 
-    let entities = if !scanner.commodities.is_empty() {
-        &mut scanner.commodities
-    } else {
-        &mut scanner.warp_nodes
-    };
+    if scanner.mode == ScanMode::Enemies {
+        let closest_enemy = unrevealed_enemies(
+            &enemy_query,
+            &indicator_query,
+            player_transform.translation,
+            scanner.range,
+        )
+        .into_iter()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
 
-    // Insert a DirectionIndicator for the first entity that still
-    // exists. The player may have already collected the commodity
-    // before it was revealed by the scanner.
+        if let Some((entity, _)) = closest_enemy {
+            reveal(
+                &mut commands,
+                &mut scanner,
+                entity,
+                enemy_indicator_settings(),
+            );
+        }
 
-    let closest_target = target_query.iter_many(entities.iter()).min_by(|a, b| {
-        let (_, a_transform, _) = a;
-        let (_, b_transform, _) = b;
-        let a_dist = a_transform
-            .translation
-            .distance_squared(player_transform.translation);
-        let b_dist = b_transform
-            .translation
-            .distance_squared(player_transform.translation);
-        a_dist.partial_cmp(&b_dist).unwrap()
-    });
+        return;
+    }
 
-    if let Some((entity, _, settings)) = closest_target {
-        commands.spawn((
-            DirectionIndicator {
-                target: entity,
-                settings: (*settings).clone(),
-            },
-            DespawnOnRestart,
-        ));
+    let range_squared = scanner.range * scanner.range;
 
-        entities
-            .iter()
-            .position(|e| *e == entity)
-            .map(|e| entities.swap_remove(e));
-    }
+    // Reveal the highest priority target in range, preferring the closest,
+    // from the first kind in the scan order that has one.
+    let next_target =
+        scanner.scan_order().into_iter().find_map(|kind| {
+            target_query
+                .iter_many(scanner.hidden(kind))
+                .filter_map(|(entity, transform, scannable, settings)| {
+                    let dist = transform
+                        .translation
+                        .distance_squared(player_transform.translation);
+
+                    (dist * scannable.difficulty * scannable.difficulty <= range_squared)
+                        .then_some((entity, dist, scannable.priority, settings))
+                })
+                .max_by(|(_, a_dist, a_priority, _), (_, b_dist, b_priority, _)| {
+                    a_priority.cmp(b_priority).then(b_dist.total_cmp(a_dist))
+                })
+        });
 
-    if entities.is_empty() {
-        scanner.timer.reset();
-        scanner.timer.pause();
+    if let Some((entity, _, _, settings)) = next_target {
+        reveal(&mut commands, &mut scanner, entity, (*settings).clone());
     }
 }
 
-/// Unpauses the scanner timer after all commodities are collected
-///
-/// TODO: this is pretty janky and does a lot of unnecessary unpausing.
-pub fn unpause(commodity_query: Query<&Commodity>, mut scanner: ResMut<Scanner>) {
-    if commodity_query.iter().len() != 0 {
+/// Reveals everything within the ping radius, then goes on cooldown
+fn ping(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut scanner: ResMut<Scanner>,
+    player_query: Query<(&Transform, &ActionState<Action>), With<Player>>,
+    target_query: Query<(Entity, &Transform, &Scannable, &DirectionIndicatorSettings)>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    indicator_query: Query<&DirectionIndicator>,
+) {
+    scanner.ping_timer.tick(time.delta());
+
+    if scanner.disabled {
         return;
     }
 
-    if scanner.warp_nodes.is_empty() {
+    let (player_transform, action_state) = player_query.single();
+
+    if !action_state.just_pressed(Action::ScannerPing) || !scanner.ping_timer.finished() {
         return;
     }
 
-    scanner.timer.unpause();
+    scanner.ping_timer.reset();
+
+    let radius_squared = scanner.ping_radius * scanner.ping_radius;
+
+    let in_radius: Vec<_> = target_query
+        .iter_many(scanner.all_hidden())
+        .filter(|(_, transform, scannable, _)| {
+            transform
+                .translation
+                .distance_squared(player_transform.translation)
+                * scannable.difficulty
+                * scannable.difficulty
+                <= radius_squared
+        })
+        .collect();
+
+    for (entity, _, _, settings) in in_radius {
+        reveal(&mut commands, &mut scanner, entity, (*settings).clone());
+    }
+
+    if scanner.mode == ScanMode::Enemies {
+        for (entity, _) in unrevealed_enemies(
+            &enemy_query,
+            &indicator_query,
+            player_transform.translation,
+            scanner.ping_radius,
+        ) {
+            reveal(
+                &mut commands,
+                &mut scanner,
+                entity,
+                enemy_indicator_settings(),
+            );
+        }
+    }
+}
+
+fn register(query: Query<(Entity, &Scannable), Added<Scannable>>, mut scanner: ResMut<Scanner>) {
+    for (entity, scannable) in query.iter() {
+        scanner.register(entity, scannable.kind);
+    }
+}
+
+fn track_pickups(mut events: EventReader<CommodityPickedUpEvent>, mut scanner: ResMut<Scanner>) {
+    for event in events.iter() {
+        scanner.set_state(event.entity, ScanState::Collected);
+    }
+}
+
+/// Treats targets that were despawned for any other reason as collected
+fn track_despawns(
+    mut removed: RemovedComponents<DirectionIndicatorSettings>,
+    mut scanner: ResMut<Scanner>,
+) {
+    for entity in removed.iter() {
+        if scanner.state(entity) != Some(ScanState::Collected) {
+            scanner.set_state(entity, ScanState::Collected);
+        }
+    }
 }
 
 pub fn proximity(
@@ -119,43 +537,18 @@ pub fn proximity(
 ) {
     let (camera, gt) = camera_query.single();
 
-    let mut remove: Option<Entity> = None;
+    let visible: Vec<_> = query
+        .iter_many(scanner.all_hidden())
+        .filter(|(_, _, transform)| {
+            let Some(ndc) = camera.world_to_ndc(gt, transform.translation) else {
+                return false;
+            };
 
-    for (entity, settings, transform) in query
-        .iter_many(&scanner.commodities)
-        .chain(query.iter_many(&scanner.warp_nodes))
-    {
-        let ndc = camera.world_to_ndc(gt, transform.translation);
-        if ndc.is_none() {
-            continue;
-        }
-        let ndc = ndc.unwrap();
+            ndc.x < 1. && ndc.y < 1. && ndc.x > -1. && ndc.y > -1.
+        })
+        .collect();
 
-        let visible = ndc.x < 1. && ndc.y < 1. && ndc.x > -1. && ndc.y > -1.;
-
-        if visible {
-            commands.spawn((
-                DirectionIndicator {
-                    target: entity,
-                    settings: (*settings).clone(),
-                },
-                DespawnOnRestart,
-            ));
-
-            remove = Some(entity);
-        }
-    }
-
-    if let Some(entity) = remove {
-        scanner
-            .commodities
-            .iter()
-            .position(|e| *e == entity)
-            .map(|e| scanner.commodities.swap_remove(e));
-        scanner
-            .warp_nodes
-            .iter()
-            .position(|e| *e == entity)
-            .map(|e| scanner.warp_nodes.swap_remove(e));
+    for (entity, settings, _) in visible {
+        reveal(&mut commands, &mut scanner, entity, (*settings).clone());
     }
 }
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{Action, Credits, GameState, Health, Planet, Player};
+
+pub struct ShieldPlugin;
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>();
+        app.add_systems(
+            Update,
+            (
+                damage,
+                recharge.after(damage),
+                update_bubble.after(recharge),
+                repair,
+                destroyed.after(damage),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Seconds after being hit before a shield starts recharging
+const RECHARGE_DELAY: f32 = 3.;
+/// Credits per point of hull repaired at a planet
+const REPAIR_COST: u32 = 2;
+/// Opacity of the shield bubble when fully charged
+const BUBBLE_ALPHA: f32 = 0.3;
+
+/// Damage dealt to a ship, which its shield absorbs before its hull
+#[derive(Event)]
+pub struct DamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+}
+
+/// Absorbs damage before it reaches the ship's `Health`. Recharges once the
+/// ship hasn't been hit for a while.
+#[derive(Component)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    /// Points regained per second
+    pub recharge_rate: f32,
+    pub delay: Timer,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            current: 0.,
+            max: 0.,
+            recharge_rate: 0.,
+            delay: Timer::from_seconds(RECHARGE_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ShieldBubble;
+
+fn damage(
+    mut events: EventReader<DamageEvent>,
+    mut query: Query<(&mut Health, Option<&mut Shield>)>,
+) {
+    for event in events.iter() {
+        let Ok((mut health, shield)) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        let mut amount = event.amount;
+
+        if let Some(mut shield) = shield {
+            let absorbed = amount.min(shield.current);
+            shield.current -= absorbed;
+            amount -= absorbed;
+
+            shield.delay.reset();
+        }
+
+        if amount > 0. {
+            health.current = (health.current - amount).max(0.);
+        }
+    }
+}
+
+fn recharge(time: Res<Time>, mut query: Query<&mut Shield>) {
+    for mut shield in query.iter_mut() {
+        if shield.current >= shield.max {
+            continue;
+        }
+
+        shield.delay.tick(time.delta());
+        if !shield.delay.finished() {
+            continue;
+        }
+
+        shield.current =
+            (shield.current + shield.recharge_rate * time.delta_seconds()).min(shield.max);
+    }
+}
+
+/// Fades the bubble out as the shield drains
+fn update_bubble(
+    query: Query<(&Shield, &Children), Changed<Shield>>,
+    mut bubble_query: Query<(&Handle<ColorMaterial>, &mut Visibility), With<ShieldBubble>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (shield, children) in query.iter() {
+        let fraction = if shield.max > 0. {
+            shield.current / shield.max
+        } else {
+            0.
+        };
+
+        for child in children.iter() {
+            let Ok((material, mut visibility)) = bubble_query.get_mut(*child) else {
+                continue;
+            };
+
+            *visibility = if fraction > 0. {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+
+            if let Some(material) = materials.get_mut(material) {
+                material.color.set_a(BUBBLE_ALPHA * fraction);
+            }
+        }
+    }
+}
+
+/// Repairs as much of the hull as the player can afford while near a planet
+fn repair(
+    mut query: Query<(&ActionState<Action>, &Transform, &mut Health, &mut Credits), With<Player>>,
+    planet_query: Query<&Transform, With<Planet>>,
+) {
+    let (action_state, player_transform, mut health, mut credits) = query.single_mut();
+
+    if !action_state.just_pressed(Action::Repair) {
+        return;
+    }
+
+    let near_planet = planet_query.iter().any(|planet| {
+        planet
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            < 150.
+    });
+    if !near_planet {
+        return;
+    }
+
+    let missing = (health.max - health.current).ceil() as u32;
+    let points = missing.min(credits.0 / REPAIR_COST);
+    if points == 0 {
+        return;
+    }
+
+    credits.0 -= points * REPAIR_COST;
+    health.current = (health.current + points as f32).min(health.max);
+}
+
+/// Losing the hull ends the run, starting it over from the first system
+fn destroyed(
+    query: Query<&Health, (Changed<Health>, With<Player>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for health in query.iter() {
+        if health.current <= 0. {
+            next_state.set(GameState::Restarting);
+        }
+    }
+}
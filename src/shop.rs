@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    loadout::{trade_in, Loadout, MODULES},
+    Action, Credits, Fonts, GameState, Planet, Player,
+};
+
+pub struct ShopPlugin;
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShopMenu>();
+
+        app.add_systems(
+            Update,
+            (toggle, click.after(toggle)).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// How close to a planet the player has to be to trade modules
+const DOCKING_RANGE: f32 = 150.;
+
+/// The shipyard, where modules are bought and sold while near a planet
+#[derive(Resource, Default)]
+pub struct ShopMenu {
+    root: Option<Entity>,
+    /// Whether actions were enabled before the shop was opened
+    actions_enabled: bool,
+    /// Whether the clock was already stopped before the shop was opened
+    time_paused: bool,
+}
+
+impl ShopMenu {
+    pub fn is_open(&self) -> bool {
+        self.root.is_some()
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum ShopButton {
+    /// Index into `MODULES`
+    Buy(usize),
+    /// Index into the player's `Loadout`
+    Sell(usize),
+    Close,
+}
+
+pub fn toggle(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    mut menu: ResMut<ShopMenu>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    mut time: ResMut<Time>,
+    player_query: Query<(&ActionState<Action>, &Transform, &Loadout, &Credits), With<Player>>,
+    planet_query: Query<&Transform, With<Planet>>,
+    fonts: Res<Fonts>,
+) {
+    let (action_state, player_transform, loadout, credits) = player_query.single();
+
+    if menu.is_open() {
+        // Actions are off while the shop is open, so it's closed with Escape
+        if keys.just_pressed(KeyCode::Escape) {
+            close(
+                &mut commands,
+                &mut menu,
+                &mut toggle_actions,
+                &mut time,
+                &mut keys,
+                &mut mouse_buttons,
+            );
+        }
+        return;
+    }
+
+    if !action_state.just_pressed(Action::OpenShop) {
+        return;
+    }
+
+    let near_planet = planet_query.iter().any(|planet| {
+        planet
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            < DOCKING_RANGE
+    });
+    if !near_planet {
+        return;
+    }
+
+    menu.root = Some(spawn_menu(&mut commands, &fonts, loadout, credits));
+    menu.actions_enabled = toggle_actions.enabled;
+    menu.time_paused = time.is_paused();
+    toggle_actions.enabled = false;
+    time.pause();
+}
+
+fn close(
+    commands: &mut Commands,
+    menu: &mut ShopMenu,
+    toggle_actions: &mut ToggleActions<Action>,
+    time: &mut Time,
+    keys: &mut Input<KeyCode>,
+    mouse_buttons: &mut Input<MouseButton>,
+) {
+    if let Some(root) = menu.root.take() {
+        commands.entity(root).despawn_recursive();
+    }
+    toggle_actions.enabled = menu.actions_enabled;
+    if !menu.time_paused {
+        time.unpause();
+    }
+
+    // Don't let the click or key that closed the shop carry on into the game
+    keys.reset_all();
+    mouse_buttons.reset_all();
+}
+
+fn spawn_menu(
+    commands: &mut Commands,
+    fonts: &Fonts,
+    loadout: &Loadout,
+    credits: &Credits,
+) -> Entity {
+    let text_style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 18.,
+        color: Color::BEIGE,
+    };
+    let heading_style = TextStyle {
+        font_size: 22.,
+        color: Color::WHITE,
+        ..text_style.clone()
+    };
+
+    // A label with a button beside it, or just the label if there's nothing
+    // to press
+    let row = |parent: &mut ChildBuilder, label: String, button: Option<(ShopButton, String)>| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(label, text_style.clone()).with_style(Style {
+                        width: Val::Px(320.),
+                        ..default()
+                    }),
+                );
+
+                let Some((kind, text)) = button else {
+                    return;
+                };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(140.),
+                                margin: UiRect::all(Val::Px(2.)),
+                                padding: UiRect::all(Val::Px(4.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        kind,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                    });
+            });
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            z_index: ZIndex::Global(8),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Shipyard",
+                TextStyle {
+                    font_size: 30.,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "{} credits. Replaced modules are traded in for half price.",
+                    credits.0
+                ),
+                text_style.clone(),
+            ));
+
+            parent.spawn(TextBundle::from_section("Installed", heading_style.clone()));
+            for (i, slot) in loadout.0.iter().enumerate() {
+                let name = slot.module.map_or("-", |module| MODULES[module].name);
+                let button = slot
+                    .module
+                    .map(|module| (ShopButton::Sell(i), format!("Sell +{}", trade_in(module))));
+
+                row(parent, format!("{:?}: {}", slot.kind, name), button);
+            }
+
+            parent.spawn(TextBundle::from_section("For Sale", heading_style.clone()));
+            for (i, module) in MODULES.iter().enumerate() {
+                let button = loadout
+                    .quote(i)
+                    .filter(|(_, price)| *price <= credits.0)
+                    .map(|(_, price)| (ShopButton::Buy(i), format!("Buy {}", price)));
+
+                row(
+                    parent,
+                    format!("{} ({:?}, {})", module.name, module.slot, module.cost),
+                    button,
+                );
+            }
+
+            row(
+                parent,
+                String::new(),
+                Some((ShopButton::Close, "Close".to_string())),
+            );
+        })
+        .id()
+}
+
+fn click(
+    mut commands: Commands,
+    query: Query<(&Interaction, &ShopButton), Changed<Interaction>>,
+    mut menu: ResMut<ShopMenu>,
+    mut player_query: Query<(&mut Loadout, &mut Credits), With<Player>>,
+    mut toggle_actions: ResMut<ToggleActions<Action>>,
+    mut time: ResMut<Time>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    fonts: Res<Fonts>,
+) {
+    let (mut loadout, mut credits) = player_query.single_mut();
+
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            ShopButton::Buy(module) => {
+                let Some((slot, price)) = loadout.quote(module) else {
+                    continue;
+                };
+                if credits.0 < price {
+                    continue;
+                }
+
+                credits.0 -= price;
+                loadout.install(slot, module);
+            }
+            ShopButton::Sell(slot) => {
+                if let Some(module) = loadout.remove(slot) {
+                    credits.0 += trade_in(module);
+                }
+            }
+            ShopButton::Close => {
+                close(
+                    &mut commands,
+                    &mut menu,
+                    &mut toggle_actions,
+                    &mut time,
+                    &mut keys,
+                    &mut mouse_buttons,
+                );
+                return;
+            }
+        }
+
+        // Redraw with the new prices and loadout
+        if let Some(root) = menu.root {
+            commands.entity(root).despawn_recursive();
+        }
+        menu.root = Some(spawn_menu(&mut commands, &fonts, &loadout, &credits));
+    }
+}
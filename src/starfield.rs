@@ -3,11 +3,14 @@ use bevy::{
     reflect::{TypePath, TypeUuid},
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
-    window::PrimaryWindow,
 };
 use interpolation::Ease;
+use rand::Rng;
 
-use crate::{warp_node::WarpAnimation, DespawnOnRestart, GameState, Player, Velocity};
+use crate::{
+    run::RunSeed, warp_node::WarpAnimation, DespawnOnRestart, GameState, MovementSet, Player,
+    Velocity,
+};
 
 pub struct StarfieldPlugin;
 #[derive(Component)]
@@ -17,25 +20,111 @@ impl Plugin for StarfieldPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(Material2dPlugin::<StarfieldMaterial>::default());
         app.add_systems(OnEnter(GameState::Playing), setup);
-        app.add_systems(Update, move_starfield.run_if(in_state(GameState::Playing)));
+        app.add_systems(
+            Update,
+            move_starfield
+                .after(MovementSet)
+                .run_if(in_state(GameState::Playing)),
+        );
         app.add_systems(Update, warp_animation.run_if(in_state(GameState::Warping)));
+        app.add_systems(
+            Update,
+            (follow_camera.after(MovementSet), twinkle)
+                .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Warping))),
+        );
     }
 }
 
+/// A layer of stars. Further layers move less as the camera moves.
+pub struct StarLayer {
+    /// How far the layer moves with the camera, from 0 (fixed) to 1
+    pub parallax: f32,
+    /// How tightly packed the stars are. Higher values mean more, smaller
+    /// stars.
+    pub density: f32,
+    /// How much stars dim and brighten over time, from 0 to 1
+    pub twinkle: f32,
+    /// Linear colour, also setting the layer's brightness
+    pub color: Color,
+}
+
+/// Most layers the shader draws
+const MAX_LAYERS: usize = 8;
+
+/// Nearest first
+const STAR_LAYERS: [StarLayer; 6] = [
+    StarLayer {
+        parallax: 1.,
+        density: 1.6,
+        twinkle: 0.,
+        color: Color::rgb_linear(0.4, 0.4, 0.4),
+    },
+    StarLayer {
+        parallax: 0.7,
+        density: 2.2,
+        twinkle: 0.1,
+        color: Color::rgb_linear(0.35, 0.35, 0.4),
+    },
+    StarLayer {
+        parallax: 0.55,
+        density: 2.8,
+        twinkle: 0.2,
+        color: Color::rgb_linear(0.4, 0.35, 0.3),
+    },
+    StarLayer {
+        parallax: 0.45,
+        density: 3.4,
+        twinkle: 0.3,
+        color: Color::rgb_linear(0.25, 0.25, 0.3),
+    },
+    StarLayer {
+        parallax: 0.4,
+        density: 4.,
+        twinkle: 0.4,
+        color: Color::rgb_linear(0.2, 0.22, 0.3),
+    },
+    StarLayer {
+        parallax: 0.35,
+        density: 4.6,
+        twinkle: 0.5,
+        color: Color::rgb_linear(0.15, 0.15, 0.2),
+    },
+];
+
+/// Tints the nebula can take, picked per system
+const NEBULA_TINTS: [Color; 5] = [
+    Color::rgb_linear(0.35, 0.1, 0.45),
+    Color::rgb_linear(0.1, 0.2, 0.45),
+    Color::rgb_linear(0.45, 0.15, 0.1),
+    Color::rgb_linear(0.1, 0.35, 0.3),
+    Color::rgb_linear(0.4, 0.3, 0.1),
+];
+
 fn setup(
     mut commands: Commands,
     mut mat2d: ResMut<Assets<StarfieldMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+    seed: Res<RunSeed>,
 ) {
-    let window = windows.single();
+    let mut rng = seed.rng("starfield");
 
+    let mut material = StarfieldMaterial {
+        layer_count: STAR_LAYERS.len() as u32,
+        nebula_color: NEBULA_TINTS[rng.gen_range(0..NEBULA_TINTS.len())],
+        nebula_strength: rng.gen_range(0.15..0.5),
+        ..default()
+    };
+    for (i, layer) in STAR_LAYERS.iter().enumerate() {
+        material.layer_colors[i] = layer.color;
+        material.layer_params[i] = Vec4::new(layer.parallax, layer.density, layer.twinkle, 0.);
+    }
+
+    // A unit quad, scaled to cover the view by `follow_camera` so that it
+    // keeps up with window resizes and zoom
     commands.spawn((
         MaterialMesh2dBundle {
-            mesh: meshes
-                .add(shape::Quad::new(Vec2::new(window.width(), window.height())).into())
-                .into(),
-            material: mat2d.add(StarfieldMaterial::default()),
+            mesh: meshes.add(shape::Quad::new(Vec2::ONE).into()).into(),
+            material: mat2d.add(material),
             transform: Transform::from_translation(Vec3::new(0., 0., crate::layer::BACKGROUND)),
             ..default()
         },
@@ -45,39 +134,52 @@ fn setup(
 }
 
 fn move_starfield(
-    query: Query<&Transform, With<Player>>,
-    mut starfield_query: Query<&mut Transform, (With<Starfield>, Without<Player>)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Starfield>)>,
     mut materials: ResMut<Assets<StarfieldMaterial>>,
 ) {
-    let player = query.single();
+    let camera = camera_query.single();
 
     for mat in materials.iter_mut() {
-        mat.1.pos = player.translation.truncate();
+        mat.1.pos = camera.translation.truncate();
     }
+}
 
+/// Keeps the starfield filling the screen as the camera moves, zooms and shakes
+fn follow_camera(
+    camera_query: Query<(&Transform, &OrthographicProjection), Without<Starfield>>,
+    mut starfield_query: Query<&mut Transform, With<Starfield>>,
+) {
+    let (camera, projection) = camera_query.single();
     let mut starfield = starfield_query.single_mut();
-    starfield.translation.x = player.translation.x;
-    starfield.translation.y = player.translation.y;
+
+    // A little oversized so that screen shake doesn't show the edges
+    let size = projection.area.size() * 1.1;
+
+    starfield.translation.x = camera.translation.x;
+    starfield.translation.y = camera.translation.y;
+    starfield.rotation = camera.rotation;
+    starfield.scale = size.extend(1.);
+}
+
+fn twinkle(time: Res<Time>, mut materials: ResMut<Assets<StarfieldMaterial>>) {
+    for mat in materials.iter_mut() {
+        mat.1.time = time.elapsed_seconds_wrapped();
+    }
 }
 
 fn warp_animation(
     mut materials: ResMut<Assets<StarfieldMaterial>>,
-    query: Query<(&Transform, &Velocity), With<Player>>,
-    mut starfield_query: Query<&mut Transform, (With<Starfield>, Without<Player>)>,
+    query: Query<&Velocity, With<Player>>,
     time: Res<Time>,
     animation: Res<WarpAnimation>,
 ) {
-    let (player_transform, player_velocity) = query.single();
+    let player_velocity = query.single();
 
     for mat in materials.iter_mut() {
         mat.1.pos += player_velocity.0
             * time.delta_seconds()
             * Vec2::splat(1. + Ease::quadratic_in(animation.starfield_timer.percent()) * 100.);
     }
-
-    let mut starfield = starfield_query.single_mut();
-    starfield.translation.x = player_transform.translation.x;
-    starfield.translation.y = player_transform.translation.y;
 }
 
 impl Material2d for StarfieldMaterial {
@@ -91,6 +193,18 @@ impl Material2d for StarfieldMaterial {
 pub struct StarfieldMaterial {
     #[uniform(0)]
     pub pos: Vec2,
+    /// Seconds, for twinkling
+    #[uniform(0)]
+    pub time: f32,
+    #[uniform(0)]
+    pub layer_count: u32,
+    #[uniform(0)]
+    pub nebula_color: Color,
+    #[uniform(0)]
+    pub nebula_strength: f32,
+    #[uniform(0)]
+    pub layer_colors: [Color; MAX_LAYERS],
+    /// Each layer's parallax, density and twinkle
     #[uniform(0)]
-    pub _wasm_padding: Vec2,
+    pub layer_params: [Vec4; MAX_LAYERS],
 }
@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    commodity::CommodityInventory,
+    direction_indicator::DirectionIndicatorSettings,
+    enemy::enemy_bundle,
+    layer,
+    physics::Interpolated,
+    run::RunSeed,
+    scanner::{ScanTargetKind, Scannable, Scanner},
+    Credits, DespawnOnRestart, GameState, Planet, Player, Velocity,
+};
+
+pub struct SystemEventPlugin;
+impl Plugin for SystemEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveSystemEvent>();
+
+        app.add_systems(OnEnter(GameState::Playing), roll);
+        app.add_systems(
+            Update,
+            (distress_beacon, pirate_ambush, solar_flare, trader_convoy)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Chance of an event happening upon entering a system
+const EVENT_CHANCE: f64 = 0.5;
+/// Pirates take a few hits to bring down, unlike the swarm
+const PIRATE_HEALTH: f32 = 3.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemEventKind {
+    DistressBeacon,
+    PirateAmbush,
+    SolarFlare,
+    TraderConvoy,
+}
+
+impl SystemEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DistressBeacon => "Distress Beacon",
+            Self::PirateAmbush => "Pirate Ambush",
+            Self::SolarFlare => "Solar Flare",
+            Self::TraderConvoy => "Trader Convoy",
+        }
+    }
+
+    pub fn objective(&self) -> &'static str {
+        match self {
+            Self::DistressBeacon => "Escort the stranded ship to the planet",
+            Self::PirateAmbush => "Destroy the pirates",
+            Self::SolarFlare => "Scanner offline until the flare passes",
+            Self::TraderConvoy => "Intercept the convoy to trade",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemEventStatus {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+pub struct SystemEvent {
+    pub kind: SystemEventKind,
+    pub status: SystemEventStatus,
+    timer: Timer,
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveSystemEvent(pub Option<SystemEvent>);
+
+impl ActiveSystemEvent {
+    fn in_progress(&self, kind: SystemEventKind) -> bool {
+        self.0
+            .as_ref()
+            .is_some_and(|e| e.kind == kind && e.status == SystemEventStatus::InProgress)
+    }
+}
+
+/// A stranded ship waiting for an escort
+#[derive(Component)]
+struct StrandedShip {
+    escorted: bool,
+}
+/// An enemy spawned by a pirate ambush
+#[derive(Component)]
+pub struct Pirate;
+#[derive(Component)]
+struct Convoy {
+    origin: Vec2,
+}
+
+fn roll(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut active: ResMut<ActiveSystemEvent>,
+    mut scanner: ResMut<Scanner>,
+    seed: Res<RunSeed>,
+) {
+    active.0 = None;
+
+    let mut rng = seed.rng("system_event");
+
+    if !rng.gen_bool(EVENT_CHANCE) {
+        return;
+    }
+
+    let kind = match rng.gen_range(0..4) {
+        0 => SystemEventKind::DistressBeacon,
+        1 => SystemEventKind::PirateAmbush,
+        2 => SystemEventKind::SolarFlare,
+        _ => SystemEventKind::TraderConvoy,
+    };
+
+    let timer = match kind {
+        // Delay before the pirates jump in
+        SystemEventKind::PirateAmbush => {
+            Timer::from_seconds(rng.gen_range(10.0..20.0), TimerMode::Once)
+        }
+        // Duration of the flare
+        SystemEventKind::SolarFlare => Timer::from_seconds(30., TimerMode::Once),
+        _ => Timer::default(),
+    };
+
+    match kind {
+        SystemEventKind::DistressBeacon => {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let pos = Vec2::from_angle(angle) * rng.gen_range(1200.0..2000.0);
+            let transform = Transform::from_translation(pos.extend(layer::SHIP));
+
+            commands.spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes.add(shape::RegularPolygon::new(14., 3).into()).into(),
+                    material: materials.add(Color::WHITE.into()),
+                    transform,
+                    ..default()
+                },
+                Interpolated::new(&transform),
+                StrandedShip { escorted: false },
+                Velocity::default(),
+                DirectionIndicatorSettings {
+                    color: Color::WHITE,
+                    label: Some("SOS".to_string()),
+                },
+                // Distress calls are loud and easy to pick up
+                Scannable {
+                    kind: ScanTargetKind::PointOfInterest,
+                    priority: 3,
+                    difficulty: 0.5,
+                },
+                DespawnOnRestart,
+            ));
+        }
+        SystemEventKind::SolarFlare => {
+            scanner.disabled = true;
+        }
+        SystemEventKind::TraderConvoy => {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let origin = Vec2::from_angle(angle) * 2500.;
+            // Head roughly across the system, missing the planet a bit
+            let heading = (-origin).rotate(Vec2::from_angle(rng.gen_range(-0.3..0.3)));
+            let transform = Transform::from_translation(origin.extend(layer::SHIP))
+                .with_rotation(Quat::from_rotation_z(heading.y.atan2(heading.x)));
+
+            commands.spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes
+                        .add(shape::Quad::new(Vec2::new(30., 14.)).into())
+                        .into(),
+                    material: materials.add(Color::GOLD.into()),
+                    transform,
+                    ..default()
+                },
+                Interpolated::new(&transform),
+                Convoy { origin },
+                Velocity(heading.normalize() * 60.),
+                DirectionIndicatorSettings {
+                    color: Color::GOLD,
+                    label: Some("Convoy".to_string()),
+                },
+                Scannable {
+                    kind: ScanTargetKind::PointOfInterest,
+                    priority: 3,
+                    difficulty: 1.,
+                },
+                DespawnOnRestart,
+            ));
+        }
+        SystemEventKind::PirateAmbush => {}
+    }
+
+    active.0 = Some(SystemEvent {
+        kind,
+        status: SystemEventStatus::InProgress,
+        timer,
+    });
+}
+
+fn distress_beacon(
+    mut commands: Commands,
+    mut active: ResMut<ActiveSystemEvent>,
+    mut query: Query<(Entity, &Transform, &mut Velocity, &mut StrandedShip), Without<Player>>,
+    mut player_query: Query<(&Transform, &mut Credits), With<Player>>,
+    planet_query: Query<&Transform, (With<Planet>, Without<Player>, Without<StrandedShip>)>,
+) {
+    if !active.in_progress(SystemEventKind::DistressBeacon) {
+        return;
+    }
+
+    let (player_transform, mut credits) = player_query.single_mut();
+    let player = player_transform.translation.truncate();
+
+    for (entity, transform, mut velocity, mut ship) in query.iter_mut() {
+        let pos = transform.translation.truncate();
+        let diff = player - pos;
+
+        if !ship.escorted {
+            if diff.length() < 60. {
+                ship.escorted = true;
+            }
+            continue;
+        }
+
+        // Follow the player, keeping a little distance
+        velocity.0 = if diff.length() > 80. {
+            diff.normalize() * 90.
+        } else {
+            Vec2::ZERO
+        };
+
+        let arrived = planet_query
+            .iter()
+            .any(|planet| planet.translation.truncate().distance(pos) < 150.);
+
+        if arrived {
+            credits.0 += 100;
+            active.0.as_mut().unwrap().status = SystemEventStatus::Completed;
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn pirate_ambush(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut active: ResMut<ActiveSystemEvent>,
+    pirate_query: Query<(), With<Pirate>>,
+    mut player_query: Query<(&Transform, &mut Credits), With<Player>>,
+) {
+    if !active.in_progress(SystemEventKind::PirateAmbush) {
+        return;
+    }
+    let event = active.0.as_mut().unwrap();
+
+    let (player_transform, mut credits) = player_query.single_mut();
+
+    event.timer.tick(time.delta());
+
+    if event.timer.just_finished() {
+        let num = 8;
+        for i in 0..num {
+            let angle = i as f32 / num as f32 * std::f32::consts::TAU;
+            let pos = player_transform.translation.truncate() + Vec2::from_angle(angle) * 450.;
+
+            commands.spawn((enemy_bundle(pos, Color::CRIMSON, PIRATE_HEALTH), Pirate));
+        }
+
+        return;
+    }
+
+    if event.timer.finished() && pirate_query.is_empty() {
+        credits.0 += 75;
+        event.status = SystemEventStatus::Completed;
+    }
+}
+
+fn solar_flare(
+    time: Res<Time>,
+    mut active: ResMut<ActiveSystemEvent>,
+    mut scanner: ResMut<Scanner>,
+) {
+    if !active.in_progress(SystemEventKind::SolarFlare) {
+        return;
+    }
+    let event = active.0.as_mut().unwrap();
+
+    event.timer.tick(time.delta());
+
+    if event.timer.just_finished() {
+        scanner.disabled = false;
+        event.status = SystemEventStatus::Completed;
+    }
+}
+
+fn trader_convoy(
+    mut commands: Commands,
+    mut active: ResMut<ActiveSystemEvent>,
+    query: Query<(Entity, &Transform, &Convoy)>,
+    mut player_query: Query<(&Transform, &mut Credits, &mut CommodityInventory), With<Player>>,
+) {
+    if !active.in_progress(SystemEventKind::TraderConvoy) {
+        return;
+    }
+
+    let (player_transform, mut credits, mut inventory) = player_query.single_mut();
+
+    for (entity, transform, convoy) in query.iter() {
+        let pos = transform.translation.truncate();
+
+        if pos.distance(player_transform.translation.truncate()) < 60. {
+            // Traders pay a premium for anything the player is carrying
+            for (_, quantity) in inventory.0.drain() {
+                credits.0 += (quantity as f32 * 1.25).round() as u32;
+            }
+
+            active.0.as_mut().unwrap().status = SystemEventStatus::Completed;
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // The convoy has passed through the system and left
+        if pos.distance(convoy.origin) > 5000. {
+            active.0.as_mut().unwrap().status = SystemEventStatus::Failed;
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 
 use crate::{
-    commodity::{CommodityInventory, CommodityPrices},
+    commodity::{CargoHold, CommodityInventory, CommodityPrices},
     direction_indicator::{DirectionIndicator, DirectionIndicatorSettings},
-    scanner::{self, Scanner},
+    flight::{Autopilot, Boost, InertialDampers},
+    scanner::{self, ScanState, ScanTargetKind, Scanner, ScannerModule},
+    shield::Shield,
+    system_event::{ActiveSystemEvent, SystemEventStatus},
     warp_node::WarpNode,
-    Credits, DespawnOnRestart, Fonts, FuelTank, GameState, Player,
+    Credits, DespawnOnRestart, Fonts, FuelTank, GameState, Health, Player,
 };
 use std::fmt::Write;
 
@@ -21,10 +24,13 @@ impl Plugin for UiPlugin {
             (
                 update_fuel,
                 update_credits,
+                update_defence,
+                update_flight,
                 update_commodity_inventory,
                 update_scanner.after(scanner::update),
                 track_warp_nodes,
                 update_warp_nodes,
+                update_system_event,
             )
                 .run_if(in_state(GameState::Playing)),
         );
@@ -36,16 +42,44 @@ struct FuelLabel;
 #[derive(Component)]
 struct CreditsLabel;
 #[derive(Component)]
+struct DefenceLabel;
+#[derive(Component)]
+struct FlightLabel;
+#[derive(Component)]
 struct CommodityInventoryLabel;
 #[derive(Component)]
 struct ScannerLabel;
 #[derive(Component)]
 struct WarpNodesLabel;
+#[derive(Component)]
+struct SystemEventLabel;
 
 #[derive(Default, Resource)]
 struct WarpNodeDisplayOrder(Vec<Entity>);
 
-fn setup(mut commands: Commands, fonts: Res<Fonts>) {
+fn setup(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    player_query: Query<
+        (
+            &FuelTank,
+            &Credits,
+            &Health,
+            &Shield,
+            &Boost,
+            &InertialDampers,
+            &Autopilot,
+            &CommodityInventory,
+            &CargoHold,
+        ),
+        With<Player>,
+    >,
+) {
+    // The player's fuel and credits carry over between systems, so start the
+    // labels with their current values rather than waiting for a change.
+    let (fuel_tank, player_credits, health, shield, boost, dampers, autopilot, inventory, hold) =
+        player_query.single();
+
     let container = commands
         .spawn((
             NodeBundle {
@@ -67,7 +101,7 @@ fn setup(mut commands: Commands, fonts: Res<Fonts>) {
         .spawn((
             TextBundle {
                 text: Text::from_section(
-                    "Fuel 0 / 50",
+                    format!("Fuel {} / {}", fuel_tank.current, fuel_tank.max),
                     TextStyle {
                         font: fonts.main.clone(),
                         font_size: 20.,
@@ -91,7 +125,7 @@ fn setup(mut commands: Commands, fonts: Res<Fonts>) {
         .spawn((
             TextBundle {
                 text: Text::from_section(
-                    "Creds 0",
+                    format!("Creds {}", player_credits.0),
                     TextStyle {
                         font: fonts.main.clone(),
                         font_size: 20.,
@@ -111,11 +145,61 @@ fn setup(mut commands: Commands, fonts: Res<Fonts>) {
         ))
         .id();
 
+    let defence = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    defence_text(health, shield),
+                    TextStyle {
+                        font: fonts.main.clone(),
+                        font_size: 20.,
+                        color: Color::SALMON,
+                    },
+                )
+                .with_alignment(TextAlignment::Right),
+                style: Style {
+                    margin: UiRect {
+                        right: Val::Px(5.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            DefenceLabel,
+        ))
+        .id();
+
+    let flight = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    flight_text(boost, dampers, autopilot),
+                    TextStyle {
+                        font: fonts.main.clone(),
+                        font_size: 20.,
+                        color: Color::CYAN,
+                    },
+                )
+                .with_alignment(TextAlignment::Right),
+                style: Style {
+                    margin: UiRect {
+                        right: Val::Px(5.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            FlightLabel,
+        ))
+        .id();
+
     let comm = commands
         .spawn((
             TextBundle {
                 text: Text::from_section(
-                    "",
+                    inventory_text(inventory, hold),
                     TextStyle {
                         font: fonts.main.clone(),
                         font_size: 20.,
@@ -189,9 +273,42 @@ fn setup(mut commands: Commands, fonts: Res<Fonts>) {
         ))
         .id();
 
-    commands
-        .entity(container)
-        .push_children(&[fuel, credits, comm, warp_nodes, scanner]);
+    let system_event = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.main.clone(),
+                        font_size: 20.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::Right),
+                style: Style {
+                    margin: UiRect {
+                        top: Val::Px(10.),
+                        right: Val::Px(5.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            SystemEventLabel,
+        ))
+        .id();
+
+    commands.entity(container).push_children(&[
+        fuel,
+        credits,
+        defence,
+        flight,
+        comm,
+        warp_nodes,
+        scanner,
+        system_event,
+    ]);
 }
 
 fn update_fuel(
@@ -216,20 +333,70 @@ fn update_credits(
     }
 }
 
+fn update_defence(
+    query: Query<(&Health, &Shield), (With<Player>, Or<(Changed<Health>, Changed<Shield>)>)>,
+    mut label_query: Query<&mut Text, With<DefenceLabel>>,
+) {
+    for (health, shield) in query.iter() {
+        for mut label in label_query.iter_mut() {
+            label.sections[0].value = defence_text(health, shield);
+        }
+    }
+}
+
+fn defence_text(health: &Health, shield: &Shield) -> String {
+    format!(
+        "Hull {:.0} / {:.0}\nShield {:.0} / {:.0}",
+        health.current, health.max, shield.current, shield.max
+    )
+}
+
+fn update_flight(
+    query: Query<
+        (&Boost, &InertialDampers, &Autopilot),
+        (
+            With<Player>,
+            Or<(Changed<Boost>, Changed<InertialDampers>, Changed<Autopilot>)>,
+        ),
+    >,
+    mut label_query: Query<&mut Text, With<FlightLabel>>,
+) {
+    for (boost, dampers, autopilot) in query.iter() {
+        for mut label in label_query.iter_mut() {
+            label.sections[0].value = flight_text(boost, dampers, autopilot);
+        }
+    }
+}
+
+fn flight_text(boost: &Boost, dampers: &InertialDampers, autopilot: &Autopilot) -> String {
+    format!(
+        "Boost {:.0}%\nDampers {}\nAutopilot {}",
+        boost.energy / boost.max_energy * 100.,
+        if dampers.enabled { "On" } else { "Off" },
+        if autopilot.engaged { "On" } else { "Off" }
+    )
+}
+
 fn update_commodity_inventory(
-    query: Query<&CommodityInventory, (Changed<CommodityInventory>, With<Player>)>,
+    query: Query<(&CommodityInventory, &CargoHold), (Changed<CommodityInventory>, With<Player>)>,
     mut label_query: Query<&mut Text, With<CommodityInventoryLabel>>,
 ) {
-    for inventory in query.iter() {
+    for (inventory, hold) in query.iter() {
         for mut label in label_query.iter_mut() {
-            label.sections[0].value = inventory.0.iter().fold(String::new(), |mut out, (k, v)| {
-                let _ = writeln!(out, "{:?} {}", k, v);
-                out
-            });
+            label.sections[0].value = inventory_text(inventory, hold);
         }
     }
 }
 
+fn inventory_text(inventory: &CommodityInventory, hold: &CargoHold) -> String {
+    let header = format!("Cargo {} / {}\n", inventory.total(), hold.0);
+
+    inventory.0.iter().fold(header, |mut out, (k, v)| {
+        let _ = writeln!(out, "{:?} {}", k, v);
+        out
+    })
+}
+
 fn track_warp_nodes(
     query: Query<&DirectionIndicator, Added<DirectionIndicator>>,
     node_query: Query<Entity, With<WarpNode>>,
@@ -243,12 +410,15 @@ fn track_warp_nodes(
 }
 
 fn update_warp_nodes(
-    query: Query<(&CommodityPrices, &DirectionIndicatorSettings)>,
+    query: Query<(&WarpNode, &CommodityPrices, &DirectionIndicatorSettings)>,
+    player_query: Query<Ref<FuelTank>, With<Player>>,
     mut text_query: Query<&mut Text, With<WarpNodesLabel>>,
     display_order: Res<WarpNodeDisplayOrder>,
     fonts: Res<Fonts>,
 ) {
-    if !display_order.is_changed() {
+    let fuel_tank = player_query.single();
+
+    if !display_order.is_changed() && !fuel_tank.is_changed() {
         return;
     }
 
@@ -267,7 +437,7 @@ fn update_warp_nodes(
         let mut sections = vec![];
 
         for entity in &display_order.0 {
-            let Ok((prices, settings)) = query.get(*entity) else {
+            let Ok((warp_node, prices, settings)) = query.get(*entity) else {
                 continue;
             };
 
@@ -279,7 +449,14 @@ fn update_warp_nodes(
                     .map_or_else(|| "?".to_string(), |l| l.clone()),
                 style_label.clone(),
             ));
-            sections.push(TextSection::new("\n".to_string(), style_label.clone()));
+
+            let cost = warp_node.fuel_cost();
+            let cost_style = if fuel_tank.current >= cost {
+                style_good.clone()
+            } else {
+                style_bad.clone()
+            };
+            sections.push(TextSection::new(format!(" {} Fuel\n", cost), cost_style));
 
             for (kind, price) in prices.0.iter() {
                 let (price_style, sign) = if *price < 1.0 {
@@ -305,14 +482,69 @@ fn update_warp_nodes(
     }
 }
 
-fn update_scanner(scanner: Res<Scanner>, mut query: Query<&mut Text, With<ScannerLabel>>) {
+fn update_scanner(
+    scanner: Res<Scanner>,
+    player_query: Query<&ScannerModule, With<Player>>,
+    mut query: Query<&mut Text, With<ScannerLabel>>,
+) {
+    let module = player_query.single();
+
     for mut text in query.iter_mut() {
-        if !scanner.timer.paused() {
+        let mut value = format!("{} [{:?}]\n", module.name, scanner.mode);
+
+        let collected = scanner.count(ScanTargetKind::Commodity, ScanState::Collected);
+        let total = collected
+            + scanner.count(ScanTargetKind::Commodity, ScanState::Hidden)
+            + scanner.count(ScanTargetKind::Commodity, ScanState::Revealed);
+        let _ = writeln!(value, "Commodities {} / {}", collected, total);
+
+        if scanner.disabled {
+            value.push_str("Scanner Offline\n");
+        } else if !scanner.timer.paused() {
             let pct = scanner.timer.percent() * 100.;
 
-            text.sections[0].value = format!("Scanning {:.0}%", pct);
+            let _ = writeln!(value, "Scanning {:.0}%", pct);
+        }
+
+        if scanner.ping_timer.finished() {
+            value.push_str("Ping Ready");
         } else {
-            text.sections[0].value = "".to_string();
+            let _ = write!(
+                value,
+                "Ping {:.0}s",
+                scanner.ping_timer.remaining_secs().ceil()
+            );
+        }
+
+        text.sections[0].value = value;
+    }
+}
+
+fn update_system_event(
+    active: Res<ActiveSystemEvent>,
+    mut query: Query<&mut Text, With<SystemEventLabel>>,
+) {
+    if !active.is_changed() {
+        return;
+    }
+
+    let value = match &active.0 {
+        Some(event) => {
+            let status = match event.status {
+                SystemEventStatus::InProgress => event.kind.objective(),
+                SystemEventStatus::Completed => "Complete",
+                SystemEventStatus::Failed => "Failed",
+            };
+            format!("{}\n{}", event.kind.name(), status)
+        }
+        None => "".to_string(),
+    };
+
+    // Event timers tick every frame, so only touch the text when what it
+    // says has changed
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
         }
     }
 }
@@ -3,7 +3,7 @@ use bevy::{
     prelude::Mesh,
     render::mesh::{Indices, PrimitiveTopology},
 };
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 pub enum Edge {
     Top,
@@ -110,11 +110,9 @@ pub fn chevron(width: f32, height: f32, thickness: f32) -> Mesh {
 }
 
 /// Generate `num` u32s with the sum of `total` and minimum value `min`
-pub fn random_u32_subdivisions(num: u32, total: u32, min: u32) -> Vec<u32> {
+pub fn random_u32_subdivisions(rng: &mut impl Rng, num: u32, total: u32, min: u32) -> Vec<u32> {
     debug_assert!(total > num * min);
 
-    let mut rng = thread_rng();
-
     let mut vals = Vec::with_capacity(num as usize);
     let mut sum = 0;
 
@@ -135,11 +133,14 @@ pub fn random_u32_subdivisions(num: u32, total: u32, min: u32) -> Vec<u32> {
 /// The absolute difference between values is at least `min_gap`.
 ///
 /// That minimum gap also "wraps" around from the last value to the first.
-pub fn random_circular_f32_distribution(num: u32, min_gap: f32, max: f32) -> Vec<f32> {
+pub fn random_circular_f32_distribution(
+    rng: &mut impl Rng,
+    num: u32,
+    min_gap: f32,
+    max: f32,
+) -> Vec<f32> {
     debug_assert!(num as f32 * min_gap < max);
 
-    let mut rng = thread_rng();
-
     let mut vals = Vec::with_capacity(num as usize);
     let mut last = 0.0;
 
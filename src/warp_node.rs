@@ -1,11 +1,16 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 use interpolation::Ease;
 use itertools::izip;
-use rand::{distributions::Uniform, thread_rng, Rng};
+use rand::{distributions::Uniform, Rng};
 
 use crate::{
-    commodity::CommodityPrices, direction_indicator::DirectionIndicatorSettings, layer,
-    scanner::Scanner, util, DespawnOnRestart, FuelTank, GameState, MovementSet, Player,
+    commodity::CommodityPrices,
+    direction_indicator::DirectionIndicatorSettings,
+    layer,
+    particle::{ParticleBurstEvent, ParticleEmitter, ParticleSettings},
+    run::RunSeed,
+    scanner::{ScanTargetKind, Scannable},
+    util, DespawnOnRestart, FuelTank, GameState, MovementSet, Player,
 };
 
 pub struct WarpNodePlugin;
@@ -26,8 +31,23 @@ impl Plugin for WarpNodePlugin {
     }
 }
 
+/// Fuel consumed per light year travelled through a warp node.
+const FUEL_PER_LIGHT_YEAR: f32 = 5.;
+
 #[derive(Component)]
-pub struct WarpNode;
+pub struct WarpNode {
+    /// Where the destination system is in the galaxy, in light years.
+    pub destination: Vec2,
+    /// Distance from this system to the destination, in light years.
+    pub distance: f32,
+}
+
+impl WarpNode {
+    pub fn fuel_cost(&self) -> u32 {
+        (self.distance * FUEL_PER_LIGHT_YEAR).ceil() as u32
+    }
+}
+
 #[derive(Component)]
 pub struct WarpFadeSprite;
 
@@ -72,94 +92,150 @@ impl Default for WarpAnimation {
 }
 
 #[derive(Resource)]
-pub struct WarpedTo(pub CommodityPrices);
+pub struct WarpedTo {
+    pub prices: CommodityPrices,
+    /// Where the system is in the galaxy, in light years
+    pub position: Vec2,
+}
 
 fn spawn_nodes(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut scanner: ResMut<Scanner>,
+    seed: Res<RunSeed>,
 ) {
-    let rng = thread_rng();
+    let mut rng = seed.rng("warp_node");
 
     let num = 3;
 
     let dist_range = Uniform::from(2600.0..3000.0);
     //let dist_range = Uniform::from(600.0..800.0);
+    let galaxy_dist_range = Uniform::from(2.0..6.0);
 
     let labels = ('A'..).take(num).map(|c| c.to_string());
-    let prices = (0..num).map(|_| CommodityPrices::new_random());
+    let prices: Vec<_> = (0..num)
+        .map(|_| CommodityPrices::new_random(&mut rng))
+        .collect();
+    let angles = util::random_circular_f32_distribution(&mut rng, num as u32, 80., 360.);
+    let galaxy_distances: Vec<f32> = (&mut rng)
+        .sample_iter(&galaxy_dist_range)
+        .take(num)
+        .collect();
     let distances = rng.sample_iter(&dist_range).take(num);
-    let angles = util::random_circular_f32_distribution(num as u32, 80., 360.);
 
-    for (angle, distance, label, price) in izip!(angles, distances, labels, prices) {
+    for (angle, distance, galaxy_distance, label, price) in
+        izip!(angles, distances, galaxy_distances, labels, prices)
+    {
         let angle = angle.to_radians();
         let (y, x) = angle.sin_cos();
         let pos = Vec3::new(x * distance, y * distance, layer::OBJECT);
+        // Each node leads off in the direction it lies in, so nodes on the
+        // same side of the system lead to systems near each other
+        let destination = seed.position + Vec2::new(x, y) * galaxy_distance;
 
-        let entity = commands
-            .spawn((
-                ColorMesh2dBundle {
-                    mesh: meshes.add(shape::Circle::new(80.).into()).into(),
-                    material: materials.add(
-                        Color::Rgba {
-                            red: 0.15,
-                            blue: 0.15,
-                            green: 0.15,
-                            alpha: 0.2,
-                        }
-                        .into(),
-                    ),
-                    transform: Transform::from_translation(pos),
-                    ..default()
-                },
-                WarpNode,
-                price,
-                DirectionIndicatorSettings {
-                    color: Color::ORANGE,
-                    label: Some(label),
-                },
-                DespawnOnRestart,
-            ))
-            .id();
-
-        scanner.warp_nodes.push(entity);
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(80.).into()).into(),
+                material: materials.add(
+                    Color::Rgba {
+                        red: 0.15,
+                        blue: 0.15,
+                        green: 0.15,
+                        alpha: 0.2,
+                    }
+                    .into(),
+                ),
+                transform: Transform::from_translation(pos),
+                ..default()
+            },
+            WarpNode {
+                destination,
+                distance: seed.position.distance(destination),
+            },
+            price,
+            DirectionIndicatorSettings {
+                color: Color::ORANGE,
+                label: Some(label),
+            },
+            Scannable {
+                kind: ScanTargetKind::WarpNode,
+                priority: 0,
+                difficulty: 1.,
+            },
+            DespawnOnRestart,
+        ));
     }
 }
 
+/// Streak particles per second trailing the ship while warping
+const WARP_PARTICLE_RATE: f32 = 120.;
+
 fn start_warp(
     mut commands: Commands,
-    query: Query<(&Transform, &CommodityPrices), With<WarpNode>>,
-    query_player: Query<(&Transform, &FuelTank), With<Player>>,
+    query: Query<(&Transform, &WarpNode, &CommodityPrices)>,
+    mut query_player: Query<(Entity, &Transform, &mut FuelTank), With<Player>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut animation: ResMut<WarpAnimation>,
+    mut particle_events: EventWriter<ParticleBurstEvent>,
 ) {
-    let (player_transform, fuel_tank) = query_player.single();
+    let (player_entity, player_transform, mut fuel_tank) = query_player.single_mut();
+
+    // Only the nearest node is entered, should the player be in reach of more
+    // than one
+    let player = player_transform.translation.truncate();
+    let Some((_, warp_node, prices)) = query
+        .iter()
+        .map(|(node, warp_node, prices)| {
+            (
+                node.translation.truncate().distance(player),
+                warp_node,
+                prices,
+            )
+        })
+        .filter(|(dist, _, _)| *dist < 80.)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+    else {
+        return;
+    };
 
-    if fuel_tank.current != fuel_tank.max {
+    let cost = warp_node.fuel_cost();
+    if fuel_tank.current < cost {
         return;
     }
 
-    for (node, prices) in query.iter() {
-        let dist = player_transform
-            .translation
-            .truncate()
-            .distance(node.translation.truncate());
-        if dist < 80. {
-            animation.starfield_timer.reset();
-
-            next_state.set(GameState::Warping);
-
-            commands.insert_resource(WarpedTo(prices.clone()));
-
-            return;
-        }
-    }
+    fuel_tank.current -= cost;
+
+    animation.starfield_timer.reset();
+
+    next_state.set(GameState::Warping);
+
+    commands.insert_resource(WarpedTo {
+        prices: prices.clone(),
+        position: warp_node.destination,
+    });
+
+    // A flash of light as the jump starts, then streaks until the destination
+    // fades in. Cleaned up when the warp ends.
+    particle_events.send(ParticleBurstEvent {
+        position: player,
+        rotation: 0.,
+        count: 60,
+        settings: ParticleSettings {
+            spread: std::f32::consts::PI,
+            ..ParticleSettings::warp()
+        },
+    });
+    commands.entity(player_entity).with_children(|parent| {
+        parent.spawn((
+            SpatialBundle::default(),
+            ParticleEmitter::new(ParticleSettings::warp(), WARP_PARTICLE_RATE),
+            DespawnOnRestart,
+        ));
+    });
 }
 
 fn warp(
     mut commands: Commands,
-    windows: Query<&Window, With<PrimaryWindow>>,
     time: Res<Time>,
     mut animation: ResMut<WarpAnimation>,
     mut fade_sprite_query: Query<&mut Sprite, With<WarpFadeSprite>>,
@@ -169,13 +245,11 @@ fn warp(
     if animation.starfield_timer.just_finished() {
         animation.fade_out_timer.unpause();
 
-        let window = windows.single();
-
+        // Sized to the view by `move_fade_sprite`
         commands.spawn((
             SpriteBundle {
                 sprite: Sprite {
                     color: Color::NONE,
-                    custom_size: Some(Vec2::new(window.width(), window.height())),
                     ..default()
                 },
                 transform: Transform::from_xyz(0., 0., layer::FADE),
@@ -225,13 +299,16 @@ fn end_warp(
 }
 
 fn move_fade_sprite(
-    camera_query: Query<&Transform, (With<Camera>, Without<WarpFadeSprite>)>,
-    mut fade_sprite_query: Query<&mut Transform, With<WarpFadeSprite>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), Without<WarpFadeSprite>>,
+    mut fade_sprite_query: Query<(&mut Transform, &mut Sprite), With<WarpFadeSprite>>,
 ) {
-    let camera = camera_query.single();
+    let (camera, projection) = camera_query.single();
 
-    for mut transform in fade_sprite_query.iter_mut() {
+    for (mut transform, mut sprite) in fade_sprite_query.iter_mut() {
         transform.translation.x = camera.translation.x;
         transform.translation.y = camera.translation.y;
+        transform.rotation = camera.rotation;
+        // A little oversized so that screen shake doesn't show the edges
+        sprite.custom_size = Some(projection.area.size() * 1.1);
     }
 }